@@ -9,6 +9,8 @@ var ascii_texture = preload("res://sprites/tilemap.png")
 var invader_texture = preload("res://sprites/invaders.png")
 var invader_data = null
 var used_count = 0
+# how far in the spectator camera zooms
+const FollowZoom = 1.5
 
 # Called when the node enters the scene tree for the first time.
 func _ready():
@@ -33,6 +35,17 @@ func clear_sprites():
 	used_count = 0
 	for n in get_children():
 		n.visible = false
+	# nothing is followed until somebody says so again
+	scale = Vector2(1.0, 1.0)
+	position = Vector2(0.0, 0.0)
+
+
+# zoom in on x, y (already flipped like sprite positions) without showing
+# anything past the edge of the playfield
+func camera_follow(x:float, y:float):
+	scale = Vector2(FollowZoom, FollowZoom)
+	var limit:Vector2 = Vector2(960.0, 540.0) * (FollowZoom - 1.0)
+	position = Vector2(clamp(-x * FollowZoom, -limit.x, limit.x), clamp(-y * FollowZoom, -limit.y, limit.y))
 
 
 func create_invader_sprite(f:int, x:float, y:float, sx:float, sy:float):
@@ -103,3 +116,14 @@ func create_text(x:float, y:float, w:String):
 		ch = w.substr(i, 1).to_ascii()[0]
 		create_ascii(ch + (15 * 256), x, y, 2.0, 2.0)
 		x += 16
+
+
+# text that stays put on screen whatever the camera does
+func create_hud_text(x:float, y:float, w:String):
+	var ch:int
+	var at:Vector2 = (Vector2(x, y) - position) / scale.x
+	var size:float = 2.0 / scale.x
+	for i in w.length():
+		ch = w.substr(i, 1).to_ascii()[0]
+		create_ascii(ch + (15 * 256), at.x, at.y, size, size)
+		at.x += 16.0 / scale.x
@@ -1,43 +1,81 @@
 extends Node2D
 
 
-var SyncedMask:BitMask = BitMask.new(55, 56);
-var XMask:BitMask = BitMask.new(0, 16);
-var LeftMask:BitMask = BitMask.new(16, 17);
-var RightMask:BitMask = BitMask.new(17, 18);
-var PrimaryMask:BitMask = BitMask.new(18, 19);
+# bit positions live in Rust, see InputLayout::Game
+var Schema = preload("res://InputSchema.gdns").new()
 
 
 var MousePosition
 var KeyLeft:bool
 var KeyRight:bool
 var KeySpace:bool
+var Steer:bool
 
+# ours on the relay, even when spectating
 var localSlot:int
 var passed
 var rust
+# waiting for the state of a match that was already running
+var joining:bool
+# a spectator joining late asks for the state on its first upload only
+var watchSent:bool
 
-func set_input():
-	for n in 64:
-		rust.custom_set_input(n, passed.Connected[n], passed.ConnectedAtStart[n], passed.Dropped[n], passed.RawInput[n])
-
-func init(l, p):
+func start(l, p):
 	localSlot = l
 	passed = p
 
 	rust = get_node("Rust")
-	set_input()
-	rust.custom_init(localSlot, passed.Seed)
+	passed.DesyncFrame = -1
 	
 	KeyLeft = false
 	KeyRight = false
 	KeySpace = false
+	Steer = false
+	watchSent = false
 	passed.NextGame = "None"
 
 
+func init(l, p):
+	start(l, p)
+	joining = false
+	var started:int = 0
+	for n in passed.ConnectedAtStart.size():
+		if passed.ConnectedAtStart[n] == true:
+			started |= 1 << n
+	rust.custom_read_packet(passed.Packet)
+	rust.custom_set_started(started)
+	rust.custom_init(-1 if passed.Spectating else localSlot, passed.Seed)
+
+
+# the match is already running, our ship shows up once we send synced input;
+# a spectator sends watch instead
+func join(l, p):
+	start(l, p)
+	joining = true
+	passed.JoinReceived = PoolByteArray()
+	rust.custom_join_begin(-1 if passed.Spectating else localSlot, localSlot)
+
+
 func main_update():
-	set_input()
+	rust.custom_read_packet(passed.Packet)
+
+	if joining == true:
+		rust.custom_join_buffer()
+		if passed.JoinReceived.size() > 0:
+			var result:int = rust.custom_join_adopt(passed.JoinReceived)
+			if result != 0:
+				passed.JoinReceived = PoolByteArray()
+			if result == 1:
+				joining = false
+		if joining == true && rust.custom_join_timed_out() == true:
+			push_error("Join snapshot never arrived, the relay may not forward them; waiting for the next match")
+			joining = false
+			passed.NextGame = "Intermission"
+		return
+
 	rust.custom_update()
+	passed.JoinSend = rust.custom_join_snapshot()
+	passed.DesyncFrame = rust.custom_desync_frame()
 	if rust.custom_game_over() == true:
 		passed.NextGame = "Intermission"
 
@@ -46,15 +84,19 @@ func copy_state():
 	rust.custom_copy()
 	pass
 	
-func fast_forward():
-	set_input()
-	rust.custom_fast_forward()
+func fast_forward(raw:int):
+	rust.custom_fast_forward(raw)
 	pass
 
 
 func process_input() -> int:
 	var ret:int = 0
 
+	if passed.Spectating == true:
+		if joining == true && watchSent == false:
+			ret |= Schema.encode("game", "watch", 1)
+		return rust.custom_desync_stamp(ret)
+
 	var X:int = MousePosition.x
 
 	var Left:int = 0
@@ -68,18 +110,27 @@ func process_input() -> int:
 	if KeySpace == true:
 		Primary = 1
 
-	ret |= XMask.Encode(X)
-	ret |= PrimaryMask.Encode(Primary)
-	ret |= LeftMask.Encode(Left)
-	ret |= RightMask.Encode(Right)
+	ret |= Schema.encode("game", "x", X)
+	ret |= Schema.encode("game", "primary", Primary)
+	ret |= Schema.encode("game", "left", Left)
+	ret |= Schema.encode("game", "right", Right)
+	ret |= Schema.encode("game", "steer", int(Steer))
+
+	ret |= Schema.encode("game", "synced", 1)
 
-	ret |= SyncedMask.Encode(1)
+	ret = rust.custom_desync_stamp(ret)
 
 	return ret;
 
 
 
 
+# the last process_input went to the relay
+func sent_input():
+	if passed.Spectating == true && joining == true:
+		watchSent = true
+
+
 func process_output(display:Object):
 	
 	rust.custom_render(display)
@@ -110,6 +161,10 @@ func _input(event):
 				KeyRight = true
 			if event.scancode == KEY_SPACE:
 				KeySpace = true
+			if event.scancode == KEY_M:
+				Steer = !Steer
+			if event.scancode == KEY_TAB && rust.custom_spectating() == true:
+				rust.custom_follow_next()
 
 		if not event.pressed:
 			if event.scancode == KEY_LEFT:
@@ -1,11 +1,8 @@
 extends Node2D
 
 
-var SyncedMask:BitMask = BitMask.new(55, 56);
-var XMask:BitMask = BitMask.new(0, 16);
-var YMask:BitMask = BitMask.new(16, 32);
-var SeedMask:BitMask = BitMask.new(32, 48);
-var ReadyMask:BitMask = BitMask.new(48, 49);
+# bit positions live in Rust, see InputLayout::Lobby
+var Schema = preload("res://InputSchema.gdns").new()
 
 var ready_data = null
 var ready_map = null
@@ -26,15 +23,21 @@ var allSynced:bool
 var allReady:bool
 
 
+# a synced slot is sending game inputs, whose desync stamp covers the lobby
+# spectator, ready and seed bits, so those only mean something while it is 0
+func in_lobby(i:int) -> bool:
+	return Schema.decode("lobby", "synced", passed.RawInput[i]) == 0
+
+
+func spectator(i:int) -> bool:
+	return in_lobby(i) == true && Schema.decode("lobby", "spectator", passed.RawInput[i]) == 1
+
+
 func should_start() -> bool:
-	var inGame:int
-	var ready:int
 	var start:bool = true
 	for i in range(0, passed.Connected.size()):
-		if passed.Connected[i] == true:
-			inGame = SyncedMask.Decode(passed.RawInput[i])
-			ready = ReadyMask.Decode(passed.RawInput[i])
-			if inGame == 1 || ready == 0:
+		if passed.Connected[i] == true && spectator(i) == false:
+			if in_lobby(i) == false || Schema.decode("lobby", "ready", passed.RawInput[i]) == 0:
 				start = false
 	return start
 
@@ -43,7 +46,7 @@ func should_end() -> bool:
 	var start:bool = true
 	for i in range(0, passed.Connected.size()):
 		if passed.ConnectedAtStart[i] == true && passed.Connected[i] == true:
-			inGame = SyncedMask.Decode(passed.RawInput[i])
+			inGame = Schema.decode("lobby", "synced", passed.RawInput[i])
 			if inGame == 0:
 				start = false;
 	return start
@@ -51,14 +54,14 @@ func should_end() -> bool:
 
 func set_ready_at_start():
 	for i in range(0, passed.Connected.size()):
-		passed.ConnectedAtStart[i] = passed.Connected[i]
+		passed.ConnectedAtStart[i] = passed.Connected[i] && spectator(i) == false
 
 
 func get_seed() -> int:
 	var rnd:int = 0
 	for i in range(0, passed.Connected.size()):
-		if passed.Connected[i] == true:
-			rnd = SeedMask.Decode(passed.RawInput[i])
+		if passed.Connected[i] == true && in_lobby(i) == true && spectator(i) == false:
+			rnd = Schema.decode("lobby", "seed", passed.RawInput[i])
 	return rnd
 
 
@@ -93,7 +96,7 @@ func main_update():
 func copy_state():
 	pass
 	
-func fast_forward():
+func fast_forward(_raw:int):
 	pass
 
 
@@ -101,8 +104,13 @@ func process_input() -> int:
 	
 	var ret:int = 0
 
+	# never synced, so the game gives us no ship
+	if passed.Spectating == true:
+		ret |= Schema.encode("lobby", "spectator", 1)
+		return ret
+
 	if allReady == true:
-		ret |= SyncedMask.Encode(1)
+		ret |= Schema.encode("lobby", "synced", 1)
 		return ret
 
 	var mouse = mousePosition
@@ -138,15 +146,19 @@ func process_input() -> int:
 
 	var rnd:int = rng.randi_range(0, 0xffff)
 
-	ret |= XMask.Encode(X)
-	ret |= YMask.Encode(Y)
-	ret |= ReadyMask.Encode(intReady)
-	ret |= SeedMask.Encode(rnd)
+	ret |= Schema.encode("lobby", "x", X)
+	ret |= Schema.encode("lobby", "y", Y)
+	ret |= Schema.encode("lobby", "ready", intReady)
+	ret |= Schema.encode("lobby", "seed", rnd)
 
 	return ret;
 
 
 
+func sent_input():
+	pass
+
+
 func process_output(display:Object):
 	
 	display.clear_sprites()
@@ -178,15 +190,13 @@ func process_output(display:Object):
 	
 	for n in range(0, passed.RawInput.size()):
 		status = "---------"
-		inGame = SyncedMask.Decode(passed.RawInput[n])
-		if passed.Connected[n] == true && inGame == 0:
+		inGame = Schema.decode("lobby", "synced", passed.RawInput[n])
+		if passed.Connected[n] == true && inGame == 0 && spectator(n) == true:
+			status = "Watching"
+		elif passed.Connected[n] == true && inGame == 0:
 			status = "In Lobby"
-			i = XMask.Decode(passed.RawInput[n])
-			j = YMask.Decode(passed.RawInput[n])
-			if i > 32767:
-				i = i - 0x10000
-			if j > 32767:
-				j = j - 0x10000
+			i = Schema.decode("lobby", "x", passed.RawInput[n])
+			j = Schema.decode("lobby", "y", passed.RawInput[n])
 			
 			xpos = float(i)
 			ypos = float(j)
@@ -201,6 +211,9 @@ func process_output(display:Object):
 	
 		display.create_text(x, -y, "Slot %d: %s" % [n, status])
 		y -= 16.0
+
+	if passed.DesyncFrame != -1:
+		display.create_text(x, -y, "Desync at frame %d!" % passed.DesyncFrame)
 	
 
 
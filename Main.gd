@@ -13,7 +13,9 @@ extends Node2D
 #var rng = RandomNumberGenerator.new()
 
 #var websocket_url = "wss://fast-forward-64.herokuapp.com/session/0"
-#var websocket_url = "ws://localhost:8080/"
+#var websocket_url = "ws://localhost:8080/session/0" # cargo run -p gm-relay
+# joining a running match needs a relay that forwards "GMJN" snapshots, only
+# gm-relay does; elsewhere the join times out and waits for the next match
 var websocket_url = "wss://go-gin-web-server-9trz.onrender.com/session/0"
 var MaxForward = 4
 var MaxDelay = 1
@@ -21,6 +23,11 @@ var MaxDelay = 1
 
 var _client = WebSocketClient.new()
 
+# bit positions live in Rust, see InputLayout
+var Schema = preload("res://InputSchema.gdns").new()
+# and so does the packet layout, see FramePacket
+var Frames = preload("res://FrameReader.gdns").new()
+
 enum {  Null = 0,
 		WaitingOnRoomSelect,
 		WaitingToConnect,
@@ -59,44 +66,34 @@ func send_input(input:int):
 	_client.get_peer(1).put_packet(GameInputUpload)
 
 
-# read in little endian I think...
+# false when there was nothing to read, or it was not a frame packet
 func read_passed() -> bool:
 	if InputQueue.size() == 0:
 		return false
 
 	LastPopped = InputQueue.pop_front()
+	var frame = Frames.decode(LastPopped)
+	if frame == null:
+		return false
 
-	var offset:int
-	var active:int
-	var broken:int
-	var raw:int
-	
-	offset = 0
-	active = 0
-	for i in 8:
-		active |= LastPopped[offset + i] << (i * 8)
-		
-	offset = 8
-	broken = 0
-	for i in 8:
-		broken |= LastPopped[offset + i] << (i * 8)
-	
-	for n in Passed.RawInput.size():
-		Passed.Connected[n] = active & (0x1 << n) != 0x0
-		Passed.Dropped[n] = broken & (0x1 << n) != 0x0
-		
-		offset = n * 7 + 16
-		raw = 0
-		for i in 7:
-			raw |= LastPopped[offset + i] << (i * 8)
-		
-		Passed.RawInput[n] = raw
-
+	Passed.Packet = LastPopped
+	Passed.Connected = frame.connected
+	Passed.Dropped = frame.dropped
+	Passed.RawInput = frame.raw
 	return true
 
 
 
 
+# somebody already went from the lobby into the game, so we are late
+func match_running() -> bool:
+	for n in Passed.RawInput.size():
+		if n != LocalSlot && Passed.Connected[n] == true:
+			if Schema.decode("lobby", "synced", Passed.RawInput[n]) == 1:
+				return true
+	return false
+
+
 # Called when the node enters the scene tree for the first time.
 func _ready():
 		
@@ -152,6 +149,9 @@ func _input(event):
 				RoomSelect = 8
 			if event.scancode == KEY_9:
 				RoomSelect = 9
+			if event.scancode == KEY_S && State == WaitingOnRoomSelect:
+				Passed.Spectating = !Passed.Spectating
+				print("Spectating: ", Passed.Spectating)
 
 
 # Called every frame. 'delta' is the elapsed time since the previous frame.
@@ -190,14 +190,18 @@ func _process(delta):
 				if UploadQueue.size() < MaxForward:
 					UploadQueue.push_back(GameInput)
 					send_input(GameInput)
+					RunningGame.sent_input()
 				
 			var done:bool = false
 			while !done:
 				if InputQueue.size() == 0:
 					done = true
-				else:
-					read_passed()
+				elif read_passed() == true:
 					if Passed.Dropped[LocalSlot] == false:
+						if match_running() == true:
+							print("Joining a running match, spectating: ", Passed.Spectating)
+							RunningGame = StateMap["GalacticMarauders"]
+							RunningGame.join(LocalSlot, Passed)
 						InputQueue.push_front(LastPopped)
 						State = Running
 						done = true
@@ -215,12 +219,14 @@ func _process(delta):
 					if UploadQueue.size() < MaxForward:
 						UploadQueue.push_back(GameInput)
 						send_input(GameInput)
+						RunningGame.sent_input()
 			
 			
 			var frameCount:int = 0
 			var dropped:int = 0
 			while InputQueue.size() > 0:
-				read_passed()
+				if read_passed() == false:
+					continue
 				
 				if Passed.Dropped[LocalSlot] == false:
 					UploadQueue.pop_front()
@@ -232,6 +238,9 @@ func _process(delta):
 				frameCount += 1
 				
 				RunningGame.main_update()
+				if Passed.JoinSend.size() > 0:
+					_client.get_peer(1).put_packet(Passed.JoinSend)
+					Passed.JoinSend = PoolByteArray()
 				if Passed.NextGame != "None":
 					RunningGame = StateMap[Passed.NextGame]
 					RunningGame.init(LocalSlot, Passed)
@@ -247,9 +256,7 @@ func _process(delta):
 					if frameCounter < UploadQueue.size() - MaxDelay:
 						#Debug.Log($"{ui.frame}");
 						# insert next player input
-						Passed.RawInput[LocalSlot] = ui;
-						Passed.Dropped[LocalSlot] = false;
-						RunningGame.fast_forward()
+						RunningGame.fast_forward(ui)
 					frameCounter += 1;
 				
 
@@ -296,7 +303,12 @@ func _connected(proto = ""):
 	#_client.get_peer(1).put_packet("Test packet".to_utf8())
 
 func _on_data():
-	InputQueue.push_back(_client.get_peer(1).get_packet())
+	var packet = _client.get_peer(1).get_packet()
+	# join snapshots skip the queue, they are not frames
+	if packet.size() > 4 && packet.subarray(0, 3).get_string_from_ascii() == "GMJN":
+		Passed.JoinReceived = packet
+	else:
+		InputQueue.push_back(packet)
 	
 		
 
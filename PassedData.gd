@@ -5,10 +5,17 @@ class_name PassedData
 var Connected
 var Dropped
 var RawInput
+var Packet
 
 var ConnectedAtStart
 var Seed:int
 var NextGame:String
+var DesyncFrame:int
+# late join, see JoinSnapshot: what we have to pass on, and what arrived
+var JoinSend
+var JoinReceived
+# chosen before picking a room: no ship, just watch whoever is playing
+var Spectating:bool
 
 func _init() -> void:
 	
@@ -24,10 +31,16 @@ func _init() -> void:
 	for n in 64:
 		RawInput.push_back(0)
 
+	Packet = PoolByteArray()
+
 	ConnectedAtStart = []
 	for n in 64:
 		ConnectedAtStart.push_back(false)
 
 	Seed = 0
 	NextGame = "None"
+	DesyncFrame = -1
+	JoinSend = PoolByteArray()
+	JoinReceived = PoolByteArray()
+	Spectating = false
 
@@ -0,0 +1,93 @@
+use crate::utility::{Bounds, CollisionMatrix};
+
+
+/***************************
+
+    Broadphase
+
+**************************/
+
+// Finds every overlapping pair of bounds.  Both strategies give exactly the
+// same pairs in exactly the same order, (i, j) with i < j sorted by i then j,
+// so contact events never depend on which one a game uses.  Pairs the
+// collision matrix rules out are skipped before their bounds are compared.
+
+#[derive(Default, Copy, Clone, PartialEq, Debug)]
+pub enum Broadphase {
+    // walk the list sorted by lower.x, stop once nothing further can overlap
+    #[default]
+    SweepAndPrune,
+    // bucket into square cells and only test bounds sharing a cell
+    Grid,
+}
+
+impl Broadphase {
+    // pixels, about the size of the bigger sprites
+    pub const CellSize: i32 = 64;
+
+    pub fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(Broadphase::SweepAndPrune),
+            1 => Some(Broadphase::Grid),
+            _ => None,
+        }
+    }
+
+    // `bounds` has to be sorted by lower.x; `cells` is scratch space
+    pub fn pairs(&self, bounds: &[Bounds], matrix: &CollisionMatrix, pairs: &mut Vec<(usize, usize)>, cells: &mut Vec<(i32, i32, usize)>) {
+        pairs.clear();
+        match self {
+            Broadphase::SweepAndPrune => Broadphase::sweep(bounds, matrix, pairs),
+            Broadphase::Grid => Broadphase::grid(bounds, matrix, pairs, cells),
+        }
+    }
+
+    fn sweep(bounds: &[Bounds], matrix: &CollisionMatrix, pairs: &mut Vec<(usize, usize)>) {
+        for (i, a) in bounds.iter().enumerate() {
+            for (j, b) in bounds.iter().enumerate().skip(i + 1) {
+                // sorted, so everything after this starts even further right
+                if b.lower.x > a.upper.x {
+                    break;
+                }
+                if matrix.collides(a.btype, b.btype) && a.overlap(b) {
+                    pairs.push((i, j));
+                }
+            }
+        }
+    }
+
+    fn grid(bounds: &[Bounds], matrix: &CollisionMatrix, pairs: &mut Vec<(usize, usize)>, cells: &mut Vec<(i32, i32, usize)>) {
+        cells.clear();
+        for (i, b) in bounds.iter().enumerate() {
+            // inclusive on both ends, the same as Bounds::overlap
+            for cx in b.lower.x.div_euclid(Broadphase::CellSize)..=b.upper.x.div_euclid(Broadphase::CellSize) {
+                for cy in b.lower.y.div_euclid(Broadphase::CellSize)..=b.upper.y.div_euclid(Broadphase::CellSize) {
+                    cells.push((cx, cy, i));
+                }
+            }
+        }
+        // sorted rather than hashed, so nothing depends on hash order
+        cells.sort_unstable();
+
+        let mut start = 0;
+        while start < cells.len() {
+            let cell = (cells[start].0, cells[start].1);
+            let mut end = start + 1;
+            while end < cells.len() && (cells[end].0, cells[end].1) == cell {
+                end += 1;
+            }
+            for (n, &(_, _, i)) in cells[start..end].iter().enumerate() {
+                for &(_, _, j) in &cells[start + n + 1..end] {
+                    if matrix.collides(bounds[i].btype, bounds[j].btype) && bounds[i].overlap(&bounds[j]) {
+                        pairs.push((i, j));
+                    }
+                }
+            }
+            start = end;
+        }
+
+        // pairs sharing several cells were found more than once
+        pairs.sort_unstable();
+        pairs.dedup();
+    }
+}
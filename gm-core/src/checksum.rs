@@ -0,0 +1,216 @@
+use crate::containers::IndexTable;
+use crate::twister::MersenneTwister;
+use crate::vector::Vector2;
+use crate::fixed::{Fixed, FixedVector2};
+use crate::components::*;
+use crate::utility::{Control, Slot, GlobalState};
+use crate::pools::{CpPack, Cp};
+
+
+/***************************
+
+    Checksum
+
+**************************/
+
+// FNV-1a over the little endian bytes of every value, so the result only
+// depends on the state and never on the platform or the build.
+#[derive(Copy, Clone)]
+pub struct Checksum {
+    value: u64,
+}
+
+impl Checksum {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    pub fn new() -> Self {
+        Self { value: Checksum::OFFSET_BASIS }
+    }
+
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.value ^= u64::from(*b);
+            self.value = self.value.wrapping_mul(Checksum::PRIME);
+        }
+    }
+
+    pub fn write_bool(&mut self, v: bool) { self.write_bytes(&[u8::from(v)]); }
+    pub fn write_u8(&mut self, v: u8) { self.write_bytes(&v.to_le_bytes()); }
+    pub fn write_i8(&mut self, v: i8) { self.write_bytes(&v.to_le_bytes()); }
+    pub fn write_u16(&mut self, v: u16) { self.write_bytes(&v.to_le_bytes()); }
+    pub fn write_i16(&mut self, v: i16) { self.write_bytes(&v.to_le_bytes()); }
+    pub fn write_u32(&mut self, v: u32) { self.write_bytes(&v.to_le_bytes()); }
+    pub fn write_i32(&mut self, v: i32) { self.write_bytes(&v.to_le_bytes()); }
+    pub fn write_i64(&mut self, v: i64) { self.write_bytes(&v.to_le_bytes()); }
+
+    // lengths are hashed as u32 so usize width never leaks into the result
+    pub fn write_len(&mut self, len: usize) {
+        self.write_u32(len.try_into().unwrap());
+    }
+}
+
+impl Default for Checksum {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+// Anything that is part of the copied game state
+pub trait StateHash {
+    fn hash(&self, sum: &mut Checksum);
+}
+
+impl<T: StateHash> StateHash for Vec<T> {
+    fn hash(&self, sum: &mut Checksum) {
+        sum.write_len(self.len());
+        for v in self {
+            v.hash(sum);
+        }
+    }
+}
+
+impl StateHash for u16 {
+    fn hash(&self, sum: &mut Checksum) {
+        sum.write_u16(*self);
+    }
+}
+
+impl StateHash for IndexTable {
+    fn hash(&self, sum: &mut Checksum) {
+        sum.write_u16(self.head);
+        self.list.hash(sum);
+    }
+}
+
+impl StateHash for MersenneTwister {
+    fn hash(&self, sum: &mut Checksum) {
+        sum.write_i32(self.p);
+        sum.write_i32(self.q);
+        sum.write_i32(self.r);
+        for x in &self.x {
+            sum.write_u32(*x);
+        }
+    }
+}
+
+impl StateHash for Vector2 {
+    fn hash(&self, sum: &mut Checksum) {
+        sum.write_i32(self.x);
+        sum.write_i32(self.y);
+    }
+}
+
+impl StateHash for Fixed {
+    fn hash(&self, sum: &mut Checksum) {
+        sum.write_i32(self.to_bits());
+    }
+}
+
+impl StateHash for FixedVector2 {
+    fn hash(&self, sum: &mut Checksum) {
+        self.x.hash(sum);
+        self.y.hash(sum);
+    }
+}
+
+impl StateHash for Animator {
+    fn hash(&self, sum: &mut Checksum) {
+        sum.write_u16(self.frame);
+        sum.write_u16(self.count);
+    }
+}
+
+impl StateHash for Body {
+    fn hash(&self, sum: &mut Checksum) {
+        self.position.hash(sum);
+        self.velocity.hash(sum);
+        self.size.hash(sum);
+    }
+}
+
+impl StateHash for Player {
+    fn hash(&self, sum: &mut Checksum) {
+        sum.write_i8(self.slot);
+        sum.write_u16(self.delayFire);
+        sum.write_u16(self.damage);
+    }
+}
+
+impl StateHash for Enemy {
+    fn hash(&self, sum: &mut Checksum) {
+        sum.write_i8(self.direction);
+        sum.write_u8(self.counter);
+        sum.write_u16(self.delayFire);
+    }
+}
+
+impl StateHash for ObjType {
+    fn hash(&self, sum: &mut Checksum) {
+        sum.write_u8(self.0);
+    }
+}
+
+impl StateHash for Cf {
+    fn hash(&self, sum: &mut Checksum) {
+        sum.write_u8(self.0);
+    }
+}
+
+impl StateHash for Control {
+    fn hash(&self, sum: &mut Checksum) {
+        sum.write_u8(self.state);
+        sum.write_i16(self.x);
+        sum.write_bool(self.nonEmpty);
+        sum.write_bool(self.left);
+        sum.write_bool(self.right);
+        sum.write_bool(self.primary);
+        sum.write_bool(self.steer);
+        sum.write_u32(self.debug);
+    }
+}
+
+impl StateHash for Slot {
+    fn hash(&self, sum: &mut Checksum) {
+        sum.write_u32(self.frame);
+        sum.write_bool(self.connected);
+        sum.write_bool(self.connectedAtStart);
+        sum.write_bool(self.broken);
+        sum.write_i64(self.raw);
+        self.input.hash(sum);
+    }
+}
+
+impl StateHash for GlobalState {
+    fn hash(&self, sum: &mut Checksum) {
+        sum.write_bool(self.playing);
+        self.enemySpeed.hash(sum);
+        sum.write_i32(self.enemyCount);
+        sum.write_u16(self.textType);
+        sum.write_i32(self.textAnimate);
+    }
+}
+
+impl StateHash for CpPack {
+    fn hash(&self, sum: &mut Checksum) {
+        self.generation.hash(sum);
+        self.comp.hash(sum);
+        self.objectId.hash(sum);
+        self.body.hash(sum);
+        self.player.hash(sum);
+        self.enemy.hash(sum);
+        self.animator.hash(sum);
+    }
+}
+
+impl StateHash for Cp {
+    fn hash(&self, sum: &mut Checksum) {
+        self.manager.hash(sum);
+        self.pack.hash(sum);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::vector::Vector2;
+use crate::fixed::FixedVector2;
+
+/***************************
+
+    Components
+
+**************************/
+#[derive(Default, Copy, Clone)]
+pub struct Animator {
+    pub frame: u16,
+    pub count: u16,
+}
+
+#[derive(Default, Copy, Clone)]
+pub struct Body {
+    pub position: FixedVector2,
+    pub velocity: FixedVector2, // per frame
+    pub size: Vector2, // whole pixels, half the width and height
+}
+
+#[derive(Default, Copy, Clone)]
+pub struct Player {
+    pub slot: i8,
+    pub delayFire: u16,
+    pub damage: u16,
+}
+
+#[derive(Default, Copy, Clone)]
+pub struct Enemy {
+    pub direction: i8,
+    pub counter: u8,
+    pub delayFire: u16,
+}
+
+#[derive(Default, Copy, Clone)]
+pub struct ObjType(pub u8);
+
+impl ObjType {
+    pub const Null: u8 = 0;
+    pub const Player: u8 = 1;
+    pub const Enemy: u8 = 2;
+    pub const Bullet: u8 = 3;
+    pub const BadBullet: u8 = 4;
+    pub const Boom: u8 = 5;
+    pub const PlayerBoom: u8 = 6;
+    pub const ShotCleaner: u8 = 7;
+    pub const Count: u8 = 8;
+    pub const USizeCount:usize = 8;
+
+    pub fn from(value: u8) -> ObjType {
+        Self(value)
+    }
+}
+
+#[derive(Default, Copy, Clone, PartialEq)]
+pub struct Cf(pub u8);
+
+impl Cf {
+    pub const None: u8 = 0;
+    pub const Component: u8 = 1 << 0;
+    pub const ObjectId: u8 = 1 << 1;
+    pub const Body: u8 = 1 << 2;
+    pub const Player: u8 = 1 << 3;
+    pub const Enemy: u8 = 1 << 4;
+    pub const Animator: u8 = 1 << 5;
+    pub const Active: u8 = 1 << 6;
+
+    pub fn from(value: u8) -> Cf {
+        Self(value)
+    }
+
+    pub fn contains(&self, mask: u8) -> bool {
+        self.0 & mask == mask
+    }
+
+    pub fn none(&self) -> bool {
+        self.0 == Cf::None
+    }
+}
+
+
@@ -0,0 +1,90 @@
+/***************************
+
+    Containers
+
+**************************/
+
+// Put new trait on Vec
+
+/*trait CpContainer {
+    //fn smartCopyTrait(&mut self, other_trait: &dyn Any);
+    fn clearTrait(&mut self);
+    fn resizeTrait(&mut self, s: usize);
+}
+
+//impl<T: 'static + Clone + Copy + Default> CpContainer for Vec<T> {
+impl<T: Clone + Copy + Default> CpContainer for Vec<T> {
+    fn smartCopyTrait(&mut self, other_trait: &dyn Any) {
+        let other_any: &dyn Any = other_trait;
+        match other_any.downcast_ref::<Vec<T>>() {
+            None => panic!("Not the right vector type!"),
+            Some(other) => {
+                self.resize(other.len(), Default::default());
+                for i in 0..other.len() { self[i] = other[i]; }        
+            }
+        };
+    }
+
+    fn clearTrait(&mut self) {
+        self.clear();
+    }
+
+    fn resizeTrait(&mut self, s: usize) {
+        if self.len() < s {
+            self.resize(s, Default::default());
+        }
+    }
+}*/
+
+// Table
+pub struct IndexTable {
+    pub head: u16,
+    pub list: Vec<u16>,
+}
+
+impl IndexTable {
+    pub const END_OF_LIST: u16 = 0;
+
+    pub fn new() -> Self {
+        Self {
+            head: IndexTable::END_OF_LIST,
+            list: vec![IndexTable::END_OF_LIST], 
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.head = IndexTable::END_OF_LIST;
+        self.list.clear();
+        self.list.push(IndexTable::END_OF_LIST);
+    }
+
+    pub fn allocate(&mut self) -> u16 {
+        let mut value = self.head;
+        if value == IndexTable::END_OF_LIST {
+            value = self.list.len().try_into().unwrap();
+            self.list.push(IndexTable::END_OF_LIST);
+        } else {
+            let index: usize = value.into();
+            self.head = self.list[index];
+            self.list[index] = IndexTable::END_OF_LIST;
+        }
+        //godot_print!("allocate {}", value);
+        value
+    }
+
+    pub fn free(&mut self, value: u16) {
+        //godot_print!("free {}", value);
+        let index: usize = value.into();
+        if self.list[index] == IndexTable::END_OF_LIST {
+            self.list[index] = self.head;
+            self.head = value;
+        }
+    }
+}
+
+impl Default for IndexTable {
+    fn default() -> Self {
+       Self::new()
+    }
+}
+
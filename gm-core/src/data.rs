@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+
+use crate::vector::Vector2;
+use crate::fixed::FixedVector2;
+use crate::components::*;
+use crate::pools::CpPrefab;
+use crate::utility::{CollisionMatrix, Event};
+use crate::pools::CpReference;
+use crate::game::Game;
+
+
+/***************************
+
+    Data
+
+**************************/
+
+// Called for a contact between the two types it was registered for, in the
+// order they were registered in.  Anything it spawns goes on the event list.
+pub type CollisionHandler = fn(&mut CpReference, &mut CpReference, &mut Vec<Event>);
+
+pub struct Data {
+    pub animation_table: HashMap<u16, u16>,
+    pub prefabs: [CpPrefab; ObjType::USizeCount],
+    pub collisions: CollisionMatrix,
+    // Game::computeKey(higher type, lower type) to the handler, and whether
+    // it wants the lower type first
+    pub collision_table: HashMap<u16, (CollisionHandler, bool)>,
+}
+
+
+impl Data {
+    pub const text_animate_counter: i32 = 8333333;
+    pub const enemy_type_count: u16 = 11;
+
+    pub const _null: u16 = 0;
+    pub const _null_persist: u16 = 1;
+    pub const enemy_00_a: u16 = 2;
+    pub const enemy_01_a: u16 = 3;
+    pub const enemy_02_a: u16 = 4;
+    pub const enemy_03_a: u16 = 5;
+    pub const enemy_04_a: u16 = 6;
+    pub const enemy_05_a: u16 = 7;
+    pub const enemy_06_a: u16 = 8;
+    pub const enemy_07_a: u16 = 9;
+    pub const enemy_08_a: u16 = 10;
+    pub const enemy_09_a: u16 = 11;
+    pub const enemy_10_a: u16 = 12;
+    pub const enemy_00_b: u16 = 13;
+    pub const enemy_01_b: u16 = 14;
+    pub const enemy_02_b: u16 = 15;
+    pub const enemy_03_b: u16 = 16;
+    pub const enemy_04_b: u16 = 17;
+    pub const enemy_05_b: u16 = 18;
+    pub const enemy_06_b: u16 = 19;
+    pub const enemy_07_b: u16 = 20;
+    pub const enemy_08_b: u16 = 21;
+    pub const enemy_09_b: u16 = 22;
+    pub const enemy_10_b: u16 = 23;
+    pub const player_ship_0: u16 = 24;
+    pub const player_ship_1: u16 = 25;
+    pub const player_shot: u16 = 26;
+    pub const enemy_shot: u16 = 27;
+    pub const easy_0: u16 = 28;
+    pub const easy_1: u16 = 29;
+    pub const player_boom_0: u16 = 30;
+    pub const player_boom_1: u16 = 31;
+    pub const player_boom_2: u16 = 32;
+    pub const player_boom_3: u16 = 33;
+    pub const player_boom_4: u16 = 34;
+    pub const player_boom_5: u16 = 35;
+    pub const player_boom_6: u16 = 36;
+    pub const enemy_boom_0: u16 = 37;
+    pub const enemy_boom_1: u16 = 38;
+    pub const enemy_boom_2: u16 = 39;
+    pub const enemy_boom_3: u16 = 40;
+    pub const enemy_boom_4: u16 = 41;
+    pub const enemy_boom_5: u16 = 42;
+    pub const enemy_boom_6: u16 = 43;
+    pub const local_player_0: u16 = 44;
+    pub const local_player_1: u16 = 45;
+    pub const text_ready: u16 = 46;
+    pub const text_no: u16 = 47;
+    pub const text_great: u16 = 48;
+    pub const target: u16 = 49;
+    pub const image_count: u16 = 50;
+    pub const _end_list: u16 = 51;
+
+    pub fn setupAnimationTable() -> HashMap<u16, u16> {
+        let mut hash = HashMap::new();
+
+        // Animations
+        let player_boom = [
+            Data::player_boom_0,
+            Data::player_boom_1,
+            Data::player_boom_2,
+            Data::player_boom_3,
+            Data::player_boom_4,
+            Data::player_boom_5,
+            Data::player_boom_6,
+            Data::_null,
+            Data::_end_list
+        ];
+        Data::registerAnimation(&mut hash, &player_boom);
+
+        let enemy_boom = [
+            Data::enemy_boom_0,
+            Data::enemy_boom_1,
+            Data::enemy_boom_2,
+            Data::enemy_boom_3,
+            Data::enemy_boom_4,
+            Data::enemy_boom_5,
+            Data::enemy_boom_6,
+            Data::_null,
+            Data::_end_list
+        ];
+        Data::registerAnimation(&mut hash, &enemy_boom);
+
+        let player = [
+            Data::player_ship_0,
+            Data::player_ship_1,
+            Data::_end_list
+        ];
+        Data::registerAnimation(&mut hash, &player);
+
+        let local_player = [
+            Data::local_player_0,
+            Data::local_player_1,
+            Data::_end_list
+        ];
+        Data::registerAnimation(&mut hash, &local_player);
+
+        let mut enemy = [
+            Data::_null,
+            Data::_null,
+            Data::_end_list
+        ];
+        for i in 0..Data::enemy_type_count {
+            let i_u16: u16 = i.try_into().unwrap();
+            enemy[0] = i_u16 + 2;
+            enemy[1] = i_u16 + 2 + Data::enemy_type_count;
+            Data::registerAnimation(&mut hash, &enemy);
+        }
+
+        hash
+    }
+
+    pub fn registerAnimation(hash: &mut HashMap<u16, u16>, list: &[u16]) {
+        let mut i = 0;
+        while list[i] != Data::_end_list {
+            if list[i] != Data::_null {
+                hash.insert(list[i], list[i + 1]);
+            }
+            i += 1;
+        }
+        let last = list[i - 1];
+        if last != Data::_null {
+            hash.insert(last, list[0]);
+        }
+    }
+
+    // a new ObjType touches nothing until it has a handler here
+    pub fn setupCollisions(&mut self) {
+        self.registerCollision(ObjType::ShotCleaner, ObjType::Bullet, Game::shotCleanerVsShot);
+        self.registerCollision(ObjType::ShotCleaner, ObjType::BadBullet, Game::shotCleanerVsShot);
+        self.registerCollision(ObjType::Bullet, ObjType::Enemy, Game::bulletVsEnemy);
+        self.registerCollision(ObjType::BadBullet, ObjType::Player, Game::badBulletVsPlayer);
+    }
+
+    // `handler` gets the `a` object first, whichever way round the contact was
+    // found; replaces any handler already registered for the pair
+    pub fn registerCollision(&mut self, a: u8, b: u8, handler: CollisionHandler) {
+        let (key, swapped) = if a >= b {
+            (Game::computeKey(a, b), false)
+        } else {
+            (Game::computeKey(b, a), true)
+        };
+        self.collisions.allow(a, b);
+        self.collision_table.insert(key, (handler, swapped));
+    }
+
+    pub fn prefab(&self, index: u8) -> CpPrefab {
+        // why can't I use index.into() here?
+        self.prefabs[usize::from(index)]
+    }
+
+    pub fn new() -> Self {
+        let mut data = Self {
+            animation_table: Data::setupAnimationTable(),
+            collisions: CollisionMatrix::new(),
+            collision_table: HashMap::new(),
+            prefabs: [
+                // null object
+                CpPrefab {
+                    comp: Cf::from(Cf::Component),
+                    objectId: ObjType::from(ObjType::Null),
+                    ..Default::default()
+                },
+
+                // player
+                CpPrefab {
+                    comp: Cf::from(Cf::Active | Cf::Component | Cf::Body | Cf::ObjectId | Cf::Player | Cf::Animator),
+                    objectId: ObjType::from(ObjType::Player),
+                    body: Body {
+                        size: Vector2::from(16, 10),
+                        ..Default::default()
+                    },
+                    player: Player {
+                        slot: -1,
+                        delayFire: 0,
+                        damage: 0,
+                    },
+                    animator: Animator {
+                        frame: Data::player_ship_0,
+                        count: 0,
+                    },
+                    ..Default::default()
+                },          
+
+                // enemy
+                CpPrefab {
+                    comp: Cf::from(Cf::Active | Cf::Component | Cf::Body | Cf::ObjectId | Cf::Enemy | Cf::Animator),
+                    objectId: ObjType::from(ObjType::Enemy),
+                    body: Body {
+                        size: Vector2::from(16, 10),
+                        ..Default::default()
+                    },
+                    enemy: Enemy {
+                        direction: 1,
+                        counter: 0,
+                        delayFire: 0,
+                    },
+                    animator: Animator {
+                        frame: Data::player_ship_0,
+                        count: 0,
+                    },
+                    ..Default::default()
+                },
+
+                // bullet
+                CpPrefab {
+                    comp: Cf::from(Cf::Active | Cf::Component | Cf::Body | Cf::ObjectId | Cf::Animator),
+                    objectId: ObjType::from(ObjType::Bullet),
+                    body: Body {
+                        velocity: FixedVector2::from_int(0, 16),
+                        size: Vector2::from(12, 20),
+                        ..Default::default()
+                    },
+                    animator: Animator {
+                        frame: Data::player_shot,
+                        count: 0,
+                    },
+                    ..Default::default()
+                },
+                        
+                // bad bullet
+                CpPrefab {
+                    comp: Cf::from(Cf::Active | Cf::Component | Cf::Body | Cf::ObjectId | Cf::Animator),
+                    objectId: ObjType::from(ObjType::BadBullet),
+                    body: Body {
+                        velocity: FixedVector2::from_int(0, -8),
+                        size: Vector2::from(7, 7),
+                        ..Default::default()
+                    },
+                    animator: Animator {
+                        frame: Data::enemy_shot,
+                        count: 0,
+                    },
+                    ..Default::default()
+                },
+
+                 // boom
+                 CpPrefab {
+                    comp: Cf::from(Cf::Active | Cf::Component | Cf::Body | Cf::ObjectId | Cf::Animator),
+                    objectId: ObjType::from(ObjType::Boom),
+                    body: Body {
+                        size: Vector2::from(14, 14),
+                        ..Default::default()
+                    },
+                    animator: Animator {
+                        frame: Data::enemy_boom_0,
+                        count: 0,
+                    },
+                    ..Default::default()
+                },
+
+                // player boom
+                CpPrefab {
+                    comp: Cf::from(Cf::Active | Cf::Component | Cf::Body | Cf::ObjectId | Cf::Animator),
+                    objectId: ObjType::from(ObjType::PlayerBoom),
+                    body: Body {
+                        size: Vector2::from(22, 21),
+                        ..Default::default()
+                    },
+                    animator: Animator {
+                        frame: Data::player_boom_0,
+                        count: 0,
+                    },
+                    ..Default::default()
+                },
+
+                // shot cleaner
+                CpPrefab {
+                    comp: Cf::from(Cf::Active | Cf::Component | Cf::Body | Cf::ObjectId),
+                    objectId: ObjType::from(ObjType::ShotCleaner),
+                    body: Body {
+                        size: Vector2::from(960, 540),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+
+            ],
+        };
+        data.setupCollisions();
+        data
+    }
+
+}
+
+impl Default for Data {
+    fn default() -> Self {
+        Self::new()
+    }
+}
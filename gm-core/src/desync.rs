@@ -0,0 +1,109 @@
+use crate::utility::{BitMask, Slot};
+use crate::input::InputLayout;
+
+
+/***************************
+
+    Desync Detection
+
+**************************/
+
+// Every Interval confirmed frames each client remembers a truncated checksum
+// of its game, and stamps the latest one into the spare bits of its upload.
+// When the stamped inputs come back confirmed, every client compares them to
+// its own record of the same checkpoint.
+#[derive(Default, Clone)]
+pub struct Desync {
+    // (checkpoint frame, truncated checksum), oldest first
+    history: Vec<(u32, u32)>,
+    // first checkpoint frame where a connected slot disagreed
+    first: Option<u32>,
+    // slot that reported the mismatching checksum
+    slot: Option<usize>,
+}
+
+impl Desync {
+    pub const Interval: u32 = 32;
+    pub const HistoryLength: usize = 8;
+
+    // spare bits above PrimaryMask
+    pub const FlagMask:BitMask = InputLayout::DesyncFlag.mask;
+    pub const FrameMask:BitMask = InputLayout::DesyncFrame.mask;
+    pub const SumMask:BitMask = InputLayout::DesyncSum.mask;
+
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.first = None;
+        self.slot = None;
+    }
+
+    pub fn truncate(checksum: u64) -> u32 {
+        (checksum & 0xffffff) as u32
+    }
+
+    fn tag(frame: u32) -> i64 {
+        i64::from((frame / Desync::Interval) & 0xff)
+    }
+
+    pub fn is_checkpoint(frame: u32) -> bool {
+        frame.is_multiple_of(Desync::Interval)
+    }
+
+    // the confirmed state's checksum after simulating a checkpoint frame
+    pub fn record(&mut self, frame: u32, checksum: u64) {
+        if self.history.len() >= Desync::HistoryLength {
+            self.history.remove(0);
+        }
+        self.history.push((frame, Desync::truncate(checksum)));
+    }
+
+    // what is left for the game to read
+    pub fn unstamped(raw: i64) -> i64 {
+        raw & !(Desync::FlagMask.mask | Desync::FrameMask.mask | Desync::SumMask.mask)
+    }
+
+    // add the latest checkpoint to an input that is about to be uploaded
+    pub fn stamp(&self, raw: i64) -> i64 {
+        let mut ret = Desync::unstamped(raw);
+        if let Some(&(frame, sum)) = self.history.last() {
+            ret |= Desync::FlagMask.encode(1);
+            ret |= Desync::FrameMask.encode(Desync::tag(frame));
+            ret |= Desync::SumMask.encode(i64::from(sum));
+        }
+        ret
+    }
+
+    // compare the stamps in this frame's confirmed inputs with our own record
+    pub fn check(&mut self, slots: &[Slot]) {
+        for (i, slot) in slots.iter().enumerate() {
+            if !slot.connected || Desync::FlagMask.decode(slot.raw) != 1 {
+                continue;
+            }
+            let tag = Desync::FrameMask.decode(slot.raw);
+            let sum = Desync::SumMask.decode(slot.raw);
+            let found = self.history.iter().find(|(frame, _)| Desync::tag(*frame) == tag);
+            if let Some(&(frame, expected)) = found {
+                if i64::from(expected) != sum && self.first.is_none_or(|f| frame < f) {
+                    self.first = Some(frame);
+                    self.slot = Some(i);
+                }
+            }
+        }
+    }
+
+    pub fn desynced(&self) -> bool {
+        self.first.is_some()
+    }
+
+    pub fn first_frame(&self) -> Option<u32> {
+        self.first
+    }
+
+    pub fn first_slot(&self) -> Option<usize> {
+        self.slot
+    }
+}
@@ -0,0 +1,50 @@
+/***************************
+
+    Entity
+
+**************************/
+#[derive(Default, Copy, Clone)]
+pub struct Entity {
+    pub index: u16,
+    pub generation: u16,
+}
+
+impl Entity {
+    pub fn new() -> Self {
+        Self { index: 0, generation: 0 }
+    }
+
+    pub fn from_index(i: usize) -> Self {
+        Self { index: i.try_into().unwrap(), generation: 0 }
+    }
+
+    // do i need this anymore?
+    pub fn from_unknown(_i: u16) -> Self {
+        Self { index: 0, generation: 0 }
+    }
+
+    pub fn from(index: u16, generation: u16) -> Self {
+        Self { index, generation }
+    }
+
+    pub fn is_null(&self) -> bool {
+        //self.index == 0 && self.generation == 0
+        self.index == 0
+    }
+
+    pub fn id(&self) -> u16 {
+        self.index
+    }
+
+    pub fn generation(&self) -> u16 {
+        self.generation
+    }
+
+    pub fn index(&self) -> usize {
+        self.index.into()
+    }
+}
+
+
+
+
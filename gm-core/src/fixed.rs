@@ -0,0 +1,289 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops;
+
+use crate::vector::Vector2;
+
+
+/***************************
+
+    Fixed
+
+**************************/
+
+// 16.16 fixed point, so speeds can be fractions of a pixel without letting
+// floats anywhere near the simulation.  The operators saturate instead of
+// wrapping or panicking; the checked_ versions say when that would happen.
+// Multiplying and dividing round toward negative infinity, on every platform.
+
+#[derive(Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed(i32);
+
+impl Fixed {
+    pub const FracBits: u32 = 16;
+    pub const Zero: Fixed = Fixed(0);
+    pub const One: Fixed = Fixed(1 << Fixed::FracBits);
+    pub const Max: Fixed = Fixed(i32::MAX);
+    pub const Min: Fixed = Fixed(i32::MIN);
+    // the largest whole number that fits
+    pub const MaxInt: i32 = i32::MAX >> Fixed::FracBits;
+    pub const MinInt: i32 = i32::MIN >> Fixed::FracBits;
+
+    pub const fn from_bits(bits: i32) -> Self {
+        Self(bits)
+    }
+
+    pub const fn to_bits(self) -> i32 {
+        self.0
+    }
+
+    // saturates outside MinInt..=MaxInt
+    pub const fn from_int(v: i32) -> Self {
+        if v > Fixed::MaxInt {
+            Fixed::Max
+        } else if v < Fixed::MinInt {
+            Fixed::Min
+        } else {
+            Self(v << Fixed::FracBits)
+        }
+    }
+
+    // num / den, e.g. from_ratio(7, 2) is 3.5
+    pub fn from_ratio(num: i32, den: i32) -> Self {
+        Fixed::from_int(num) / Fixed::from_int(den)
+    }
+
+    // rounds down, so -0.5 is -1
+    pub const fn to_int(self) -> i32 {
+        self.0 >> Fixed::FracBits
+    }
+
+    // halves round up
+    pub const fn round(self) -> i32 {
+        (self.0 >> Fixed::FracBits) + ((self.0 >> (Fixed::FracBits - 1)) & 1)
+    }
+
+    // only for rendering, never feed it back into the simulation
+    pub fn to_f32(self) -> f32 {
+        (self.0 as f32) / (Fixed::One.0 as f32)
+    }
+
+    pub fn abs(self) -> Self {
+        Self(self.0.saturating_abs())
+    }
+
+    // rounds down, Zero for negative numbers
+    pub fn sqrt(self) -> Self {
+        let Ok(bits) = u64::try_from(self.0) else {
+            return Fixed::Zero;
+        };
+        // sqrt(bits / 2^16) * 2^16 == sqrt(bits * 2^16), which always fits
+        Self(i32::try_from((bits << Fixed::FracBits).isqrt()).unwrap())
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.0.checked_sub(other.0).map(Self)
+    }
+
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        let wide = (i64::from(self.0) * i64::from(other.0)) >> Fixed::FracBits;
+        i32::try_from(wide).ok().map(Self)
+    }
+
+    // None for division by zero too
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        if other.0 == 0 {
+            return None;
+        }
+        let a = i64::from(self.0) << Fixed::FracBits;
+        let b = i64::from(other.0);
+        // `/` rounds toward zero, step down when the true result is negative
+        let mut wide = a / b;
+        if a % b != 0 && (a < 0) != (b < 0) {
+            wide -= 1;
+        }
+        i32::try_from(wide).ok().map(Self)
+    }
+
+    pub fn saturating_mul(self, other: Self) -> Self {
+        self.checked_mul(other).unwrap_or(Fixed::saturate(self.0.signum() * other.0.signum()))
+    }
+
+    // dividing by zero gives Max or Min by the sign of self, or Zero
+    pub fn saturating_div(self, other: Self) -> Self {
+        if other.0 == 0 {
+            return Fixed::saturate(self.0.signum());
+        }
+        self.checked_div(other).unwrap_or(Fixed::saturate(self.0.signum() * other.0.signum()))
+    }
+
+    fn saturate(sign: i32) -> Self {
+        match sign.cmp(&0) {
+            Ordering::Less => Fixed::Min,
+            Ordering::Equal => Fixed::Zero,
+            Ordering::Greater => Fixed::Max,
+        }
+    }
+}
+
+impl fmt::Display for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", f64::from(self.0) / f64::from(Fixed::One.0))
+    }
+}
+
+impl fmt::Debug for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Fixed({})", self)
+    }
+}
+
+impl ops::Neg for Fixed {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(self.0.saturating_neg())
+    }
+}
+
+impl ops::Add for Fixed {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self(self.0.saturating_add(other.0))
+    }
+}
+
+impl ops::Sub for Fixed {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self(self.0.saturating_sub(other.0))
+    }
+}
+
+impl ops::Mul for Fixed {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        self.saturating_mul(other)
+    }
+}
+
+impl ops::Div for Fixed {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        self.saturating_div(other)
+    }
+}
+
+impl ops::Mul<i32> for Fixed {
+    type Output = Self;
+
+    fn mul(self, other: i32) -> Self {
+        Self(self.0.saturating_mul(other))
+    }
+}
+
+impl ops::AddAssign for Fixed {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl ops::SubAssign for Fixed {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+
+/***************************
+
+    FixedVector2
+
+**************************/
+
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct FixedVector2 {
+    pub x: Fixed,
+    pub y: Fixed,
+}
+
+impl ops::Neg for FixedVector2 {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self { x: -self.x, y: -self.y }
+    }
+}
+
+impl ops::Add for FixedVector2 {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self { x: self.x + other.x, y: self.y + other.y }
+    }
+}
+
+impl ops::Sub for FixedVector2 {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self { x: self.x - other.x, y: self.y - other.y }
+    }
+}
+
+impl ops::AddAssign for FixedVector2 {
+    fn add_assign(&mut self, other: Self) {
+        self.x += other.x;
+        self.y += other.y;
+    }
+}
+
+impl ops::SubAssign for FixedVector2 {
+    fn sub_assign(&mut self, other: Self) {
+        self.x -= other.x;
+        self.y -= other.y;
+    }
+}
+
+impl FixedVector2 {
+    pub fn new() -> Self {
+        Self { x: Fixed::Zero, y: Fixed::Zero }
+    }
+
+    pub fn from(x: Fixed, y: Fixed) -> Self {
+        Self { x, y }
+    }
+
+    pub fn from_int(x: i32, y: i32) -> Self {
+        Self { x: Fixed::from_int(x), y: Fixed::from_int(y) }
+    }
+
+    pub fn from_pixels(v: Vector2) -> Self {
+        FixedVector2::from_int(v.x, v.y)
+    }
+
+    // rounds down, like Fixed::to_int
+    pub fn to_pixels(self) -> Vector2 {
+        Vector2::from(self.x.to_int(), self.y.to_int())
+    }
+
+    pub fn scale(self, s: Fixed) -> Self {
+        Self { x: self.x * s, y: self.y * s }
+    }
+
+    // rounds down, saturates at Fixed::Max
+    pub fn length(self) -> Fixed {
+        let x = i64::from(self.x.to_bits()).unsigned_abs();
+        let y = i64::from(self.y.to_bits()).unsigned_abs();
+        let bits = (x * x + y * y).isqrt();
+        Fixed::from_bits(i32::try_from(bits).unwrap_or(i32::MAX))
+    }
+}
@@ -0,0 +1,767 @@
+use std::convert::TryFrom;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use crate::fixed::{Fixed, FixedVector2};
+use crate::components::*;
+use crate::utility::*;
+use crate::input::InputLayout;
+use crate::twister::MersenneTwister;
+use crate::pools::{Cp, CpReference};
+use crate::data::Data;
+use crate::checksum::{Checksum, StateHash};
+use crate::desync::Desync;
+use crate::broadphase::Broadphase;
+use crate::packet::{FramePacket, PacketError};
+use crate::snapshot::{Snapshot, SnapshotError, SnapshotReader, SnapshotWriter};
+
+
+/***************************
+
+    Systems
+
+**************************/
+
+/*
+struct EnemySystem { 
+
+
+}
+
+
+impl EnemySystem {
+    fn execute(game: &mut Game) {
+        let pack_mut = SliceBodyEnemy {
+            comp: game.components.pack.comp.as_slice(),
+            body: game.components.pack.body.as_mut_slice(),
+            enemy: game.components.pack.enemy.as_mut_slice(),
+        };
+
+        for r in pack_mut.iter_mut() {
+            r.enemy.counter += 1;
+            if r.enemy.counter > 150 {
+                r.enemy.counter = 0;
+                r.enemy.direction = -r.enemy.direction;
+            }
+
+            let pr = ent.get(player);
+
+            r.body.velocity.x = game.global.enemySpeed * i32::from(r.enemy.direction);
+
+            if r.enemy.delayFire > 0 {
+                r.enemy.delayFire -= 1;
+            }
+
+            if r.enemy.delayFire == 0 {
+                r.enemy.delayFire = 2000;
+                game.eventList.push( Event::from_entity(ObjType::BadBullet, r.body.position ) );
+            }
+        }
+    }
+}
+*/
+
+// How fastForward predicts frames that are not confirmed yet
+#[derive(Default, Copy, Clone, PartialEq)]
+pub enum PredictionMode {
+    // movement and animation only, contacts and events wait for the real update
+    #[default]
+    Fast,
+    // the complete update, so predicted shots and explosions show up right away
+    Full,
+}
+
+// How ships are moved, chosen for the whole match before Game::init
+#[derive(Default, Copy, Clone, PartialEq)]
+pub enum Steering {
+    // every player picks with the steer bit of their input
+    #[default]
+    PerPlayer,
+    // left and right only
+    Keys,
+    // toward the X in every input
+    Target,
+}
+
+impl Steering {
+    pub fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(Steering::PerPlayer),
+            1 => Some(Steering::Keys),
+            2 => Some(Steering::Target),
+            _ => None,
+        }
+    }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            Steering::PerPlayer => 0,
+            Steering::Keys => 1,
+            Steering::Target => 2,
+        }
+    }
+}
+
+// What a slot the server marked dropped does on that frame.  Its raw input is
+// whatever the relay repeated, so it is never read.
+#[derive(Default, Copy, Clone, PartialEq)]
+pub enum BrokenInput {
+    // keep doing what the last good frame did
+    #[default]
+    Hold,
+    // let go of everything
+    Neutral,
+}
+
+impl BrokenInput {
+    pub fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(BrokenInput::Hold),
+            1 => Some(BrokenInput::Neutral),
+            _ => None,
+        }
+    }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            BrokenInput::Hold => 0,
+            BrokenInput::Neutral => 1,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Game {
+    // static data, shared by every copy so registrations reach all of them
+    pub data: Rc<Data>,
+
+    // intermediate data, no need to copy
+    pub boundList: Vec<Bounds>,
+    pub pairList: Vec<(usize, usize)>,
+    pub cellList: Vec<(i32, i32, usize)>,
+    pub eventList: Vec<Event>,
+    pub joined: u64, // slots spawned late or starting to watch by the last update
+
+    // must be set from outside every frame
+    pub slots: Vec<Slot>,
+
+    // settings, never copied
+    pub predictionMode: PredictionMode,
+    pub broadphase: Broadphase, // any choice gives the same contacts
+
+    // must be copied
+    pub steering: Steering, // kept by init
+    pub brokenInput: BrokenInput, // kept by init
+    pub frame: u32, // frames simulated since init
+    pub gameOver: bool,
+    pub global: GlobalState,
+    pub rand: MersenneTwister,
+    
+    // component lists
+    pub components: Cp,
+
+    // diagnostics, never copied
+    pub desync: Desync,
+}
+
+impl Game {
+
+    // see InputLayout::Game
+    pub const SyncedMask:BitMask = InputLayout::Synced.mask;
+    pub const XMask:BitMask = InputLayout::X.mask;
+    pub const LeftMask:BitMask = InputLayout::Left.mask;
+    pub const RightMask:BitMask = InputLayout::Right.mask;
+    pub const PrimaryMask:BitMask = InputLayout::Primary.mask;
+    pub const SteerMask:BitMask = InputLayout::Steer.mask;
+    pub const WatchMask:BitMask = InputLayout::Watch.mask;
+
+    // same top speed as the keys
+    pub const MaxSteer: Fixed = Fixed::from_int(5);
+
+    pub const SlotCount: usize = 64;
+
+    pub fn new() -> Self {
+        Self {
+            slots: vec![Default::default(); Game::SlotCount],
+            ..Default::default()
+        }
+    }
+
+    // register collision handlers on the Data before sharing it
+    pub fn with_data(data: Rc<Data>) -> Self {
+        Self {
+            data,
+            ..Game::new()
+        }
+    }
+
+    // it's interesting that I can't use this function
+    //fn iter_filter(&mut self, mask: u8) -> CpIterMut {
+    //    self.components.iter_filter(mask)
+    //}
+
+    // need to use "as" because this is a const function!  Thankfully this can't overflow
+    pub const fn computeKey(a: u8, b: u8) -> u16 { ((a as u16) << 8) | (b as u16) }   
+    pub fn collisionFunction(game: &mut Self, index: usize) {
+        let e = game.eventList[index];
+        let Some(&(handler, swapped)) = game.data.collision_table.get(&e.key) else {
+            // no logic for collision!
+            return;
+        };
+        let mut iter = game.components.iter();
+        let (mut a, mut b) = iter.nth_double(e.a, e.b).unwrap();
+        // hand them over in the order the handler was registered with
+        if swapped == true {
+            handler(&mut b, &mut a, &mut game.eventList);
+        } else {
+            handler(&mut a, &mut b, &mut game.eventList);
+        }
+    }
+
+    // the built in collisions, see Data::setupCollisions
+
+    pub fn shotCleanerVsShot(_cleaner: &mut CpReference, shot: &mut CpReference, _events: &mut Vec<Event>) {
+        if shot.animator.frame != Data::_null
+        {
+            shot.animator.frame = Data::_null;
+        }
+    }
+
+    pub fn bulletVsEnemy(bullet: &mut CpReference, enemy: &mut CpReference, events: &mut Vec<Event>) {
+        if bullet.animator.frame != Data::_null && enemy.animator.frame != Data::_null
+        {
+            bullet.animator.frame = Data::_null;
+            enemy.animator.frame = Data::_null;
+            events.push( Event::from_entity(ObjType::Boom, enemy.body.position ) );
+        }
+    }
+
+    pub fn badBulletVsPlayer(bullet: &mut CpReference, player: &mut CpReference, events: &mut Vec<Event>) {
+        if bullet.animator.frame != Data::_null && player.animator.frame != Data::_null_persist
+        {
+            bullet.animator.frame = Data::_null;
+
+            player.player.damage = 100;
+            player.animator.frame = Data::_null_persist;
+
+            events.push( Event::from_entity(ObjType::PlayerBoom, player.body.position ) );
+        }
+    }
+    
+    
+    pub fn smartCopy(&mut self, other: &Game) {
+        self.slots.resize(other.slots.len(), Default::default());
+        self.slots.as_mut_slice().copy_from_slice(&other.slots.as_slice());
+        self.steering = other.steering;
+        self.brokenInput = other.brokenInput;
+        self.frame = other.frame;
+        self.global = other.global;
+        self.rand = other.rand;
+        self.components.smartCopy(&other.components);
+        self.gameOver = other.gameOver;
+        // the same rules, but not state, so never hashed
+        self.data = Rc::clone(&other.data);
+
+        // other always resolved its events, anything left here is a stale prediction
+        self.eventList.clear();
+    }
+
+    // hashes exactly what smartCopy copies
+    pub fn checksum(&self) -> u64 {
+        let mut sum = Checksum::new();
+        sum.write_u8(self.steering.to_u8());
+        sum.write_u8(self.brokenInput.to_u8());
+        sum.write_u32(self.frame);
+        self.slots.hash(&mut sum);
+        self.global.hash(&mut sum);
+        self.rand.hash(&mut sum);
+        self.components.hash(&mut sum);
+        sum.write_bool(self.gameOver);
+        sum.value()
+    }
+
+    // fills the slots from one of the relay server's frame packets
+    pub fn read_packet(&mut self, bytes: &[u8]) -> Result<(), PacketError> {
+        FramePacket::decode(bytes)?.apply(&mut self.slots);
+        Ok(())
+    }
+
+    // serializes exactly what smartCopy copies
+    pub fn save_snapshot(&self) -> Vec<u8> {
+        let mut w = SnapshotWriter::new();
+        w.write_u8(self.steering.to_u8());
+        w.write_u8(self.brokenInput.to_u8());
+        w.write_u32(self.frame);
+        self.slots.save(&mut w);
+        self.global.save(&mut w);
+        self.rand.save(&mut w);
+        self.components.save(&mut w);
+        w.write_bool(self.gameOver);
+        w.finish()
+    }
+
+    // leaves the game untouched unless the whole snapshot is valid
+    pub fn load_snapshot(&mut self, bytes: &[u8]) -> Result<(), SnapshotError> {
+        let mut r = SnapshotReader::new(bytes)?;
+        let steering = Steering::from_u8(r.read_u8()?).ok_or(SnapshotError::Invalid("unknown steering"))?;
+        let brokenInput = BrokenInput::from_u8(r.read_u8()?).ok_or(SnapshotError::Invalid("unknown broken input"))?;
+        let frame = r.read_u32()?;
+        let slots: Vec<Slot> = Vec::load(&mut r)?;
+        let global = GlobalState::load(&mut r)?;
+        let rand = MersenneTwister::load(&mut r)?;
+        let components = Cp::load(&mut r)?;
+        let gameOver = r.read_bool()?;
+        r.finish()?;
+
+        if slots.len() != Game::SlotCount {
+            return Err(SnapshotError::Invalid("wrong number of slots"));
+        }
+        for i in 0..components.pack.size() {
+            let slot = components.pack.player[i].slot;
+            if components.pack.comp[i].contains(Cf::Player) && usize::try_from(slot).map_or(true, |s| s >= slots.len()) {
+                return Err(SnapshotError::Invalid("player slot out of range"));
+            }
+        }
+
+        self.steering = steering;
+        self.brokenInput = brokenInput;
+        self.frame = frame;
+        self.slots = slots;
+        self.global = global;
+        self.rand = rand;
+        self.components = components;
+        self.gameOver = gameOver;
+        Ok(())
+    }
+    
+
+    
+    pub fn init(&mut self, seed: u32) {
+
+        self.rand.set_seed(seed);
+        self.desync.reset();
+        self.frame = 0;
+
+        self.gameOver = false; // do I need this now?
+
+        self.global.playing = false;
+        self.global.enemySpeed = Fixed::from_int(3); // 3
+        self.global.enemyCount = 0;
+        self.global.textType = Data::text_ready;
+        self.global.textAnimate = 0;
+
+        //self.slots.resize(64, Default::default());
+
+        self.components.clear();
+
+        self.eventList.clear();
+        // nothing to hold on to from a match before this one
+        for slot in &mut self.slots {
+            slot.input = Control::default();
+        }
+        for usize_j in 0..self.slots.len() {
+            // if slot is connected
+            if self.slots[usize_j].connected == true && self.slots[usize_j].connectedAtStart == true
+            {
+                let j: i32 = usize_j.try_into().unwrap();
+                let j16: u16 = usize_j.try_into().unwrap();
+                self.eventList.push( Event::from_player(j16, FixedVector2::from_int(j * 60 - 960 + 32, -500) ) );
+            }
+        }
+
+        // shot cleaners make sure shots don't last forever
+        self.eventList.push( Event::from_entity(ObjType::ShotCleaner, FixedVector2::from_int(0,  1090) ) );
+        self.eventList.push( Event::from_entity(ObjType::ShotCleaner, FixedVector2::from_int(0, -1090) ) );
+
+        self.resolveEvents();
+
+    }
+
+    pub fn compute_input(&mut self) {
+        for slot in &mut self.slots {
+            if slot.broken == true {
+                match self.brokenInput {
+                    BrokenInput::Hold => {}
+                    BrokenInput::Neutral => slot.input = Control::default(),
+                }
+                continue;
+            }
+            slot.input.primary = Game::PrimaryMask.decode(slot.raw) == 1;
+            slot.input.left = Game::LeftMask.decode(slot.raw) == 1;
+            slot.input.right = Game::RightMask.decode(slot.raw) == 1;
+            slot.input.x = Game::XMask.decode(slot.raw).try_into().unwrap();
+            slot.input.steer = Game::SteerMask.decode(slot.raw) == 1;
+        }
+    }
+
+    pub fn resolveState(&mut self) {
+        if self.global.playing == false
+        {
+            self.global.textAnimate += Data::text_animate_counter;
+
+            if self.global.textAnimate > 1000000000
+            {
+                // this was for when the game actually quit back to the lobby!
+                if self.global.textType != Data::text_ready
+                {
+                    self.gameOver = true;
+                }
+                else
+                {
+                    self.global.playing = true;
+
+                    // fix all ships
+                    //Targets.Clear();
+
+                    for r in self.components.pack.filter(Cf::Active | Cf::Player) {
+                        let i: usize = r.player.slot.try_into().unwrap();
+                        if self.slots[i].connected == true {
+                            r.player.damage = 0;
+                            r.animator.frame = Data::player_ship_0;
+                        }
+                    }
+
+                    // j 24, i 20
+                    // create new set of entities
+                    for j_usize in 0..24
+                    {
+                        for i_usize in 0..20
+                        {
+                            let i: i32 = i_usize.try_into().unwrap();
+                            let j: i32 = j_usize.try_into().unwrap();
+                            self.eventList.push( Event::from_entity(ObjType::Enemy, FixedVector2::from_int(j * 60 - 960 + 32, i * 32 - 100) ) );
+                        }
+                    }
+                }
+            }
+        }
+    }
+    
+
+    pub fn update(&mut self) {
+        self.desync.check(&self.slots);
+        self.simulate();
+        self.checkpoint();
+    }
+
+    // the full pipeline without desync bookkeeping, for frames that may be rolled back
+    pub fn simulate(&mut self) {
+        self.advanceFrame();
+        self.compute_input();
+        self.spawnLateJoiners();
+        self.updateAnimators();
+        self.updatePlayers();
+        self.updateEnemies();        
+        self.integrate();
+        self.resolveState();   
+        self.fillContactList();
+        self.resolveEvents();
+    }
+
+    // call once the frame just simulated is known to be confirmed
+    pub fn checkpoint(&mut self) {
+        if Desync::is_checkpoint(self.frame) {
+            let sum = self.checksum();
+            self.desync.record(self.frame, sum);
+        }
+    }
+
+    // number the frame about to be simulated, and the inputs it runs with
+    fn advanceFrame(&mut self) {
+        self.frame += 1;
+        for slot in &mut self.slots {
+            slot.frame = self.frame;
+        }
+    }
+
+    pub fn fastForward(&mut self) {
+        if self.predictionMode == PredictionMode::Full {
+            // exactly what a confirmed frame runs, minus desync bookkeeping
+            self.simulate();
+            return;
+        }
+        self.advanceFrame();
+        self.compute_input();
+        self.updateAnimators();
+        self.updatePlayers();
+        self.updateEnemies();
+        self.integrate();
+        self.resolveState();
+    }
+
+    
+    pub fn fillContactList(&mut self) {
+        // clear the bounds list
+        self.boundList.clear();
+
+        // fill up the bounds list with objects
+        for r in self.components.filter(Cf::Active | Cf::Body) {
+            // booms and the like touch nothing, leave them out of the sweep
+            if self.data.collisions.any(r.objectId.0) == false {
+                continue;
+            }
+            // integrate already moved it, so it started the frame a velocity back
+            let p = r.body.position.to_pixels();
+            let d = p - (r.body.position - r.body.velocity).to_pixels();
+            self.boundList.push( Bounds::from(r.entity, r.objectId.0, p, d, r.body.size) );
+        }
+
+        // sort it here!
+        //self.boundList.sort();
+
+        self.boundList.sort_by(|a, b| {
+            if a.lower.x < b.lower.x {
+                Ordering::Less
+            } else if a.lower.x == b.lower.x {
+                Ordering::Equal
+            } else {
+                Ordering::Greater
+            }
+        });
+
+        // collision overlap code
+        self.broadphase.pairs(&self.boundList, &self.data.collisions, &mut self.pairList, &mut self.cellList);
+        let first = self.eventList.len();
+        for &(i, j) in &self.pairList {
+            let iter = &self.boundList[i];
+            let nextIter = &self.boundList[j];
+            // the broadphase only knows the paths' boxes overlap
+            let Some(time) = iter.sweep(nextIter) else {
+                continue;
+            };
+            let it: u16 = iter.btype.into();
+            let nxit: u16 = nextIter.btype.into();
+            if iter.btype > nextIter.btype {
+                self.eventList.push( Event::from_contact( (it << 8) | nxit, iter.entity, nextIter.entity, time ) );
+            } else {
+                self.eventList.push( Event::from_contact( (nxit << 8) | it, nextIter.entity, iter.entity, time ) );
+            }
+        }
+        // earliest hit first, so a shot through two enemies gets the nearer one;
+        // the sort is stable, ties keep the broadphase order
+        self.eventList[first..].sort_by_key(|e| e.time);
+
+    }
+    
+
+    pub fn integrate(&mut self) {
+        for r in self.components.filter(Cf::Active | Cf::Body) {
+            r.body.position += r.body.velocity;
+        }
+    }
+
+    
+    pub fn updateAnimators(&mut self) {
+        for r in self.components.filter(Cf::Active | Cf::Animator) {
+            r.animator.count += 1;
+            if r.animator.count > 3 {
+                r.animator.count = 0;
+                let find_it = self.data.animation_table.get(&r.animator.frame);
+                if find_it != None {
+                    r.animator.frame = *(find_it.unwrap());
+                }
+            }
+            if r.animator.frame == Data::_null {
+                self.eventList.push( Event::from_destroy(r.entity) );
+            }
+        }
+    }
+
+
+    pub fn updateEnemies(&mut self) {
+        self.global.enemyCount = 0;
+
+        for r in self.components.filter(Cf::Active | Cf::Body | Cf::Enemy) {
+            r.enemy.counter += 1;
+            if r.enemy.counter > 150 {
+                r.enemy.counter = 0;
+                r.enemy.direction = -r.enemy.direction;
+            }
+
+            r.body.velocity.x = self.global.enemySpeed * i32::from(r.enemy.direction);
+
+            if r.enemy.delayFire > 0 {
+                r.enemy.delayFire -= 1;
+            }
+
+            if r.enemy.delayFire == 0 {
+                r.enemy.delayFire = 2000;
+                self.eventList.push( Event::from_entity(ObjType::BadBullet, r.body.position ) );
+            }
+
+            self.global.enemyCount += 1;
+        }
+        // calculate enemy speed based on count
+        self.global.enemySpeed = Fixed::from_int(3);
+
+        // if enemy count is zero, set playing to false, text to great job!
+        if self.global.playing == true && self.global.enemyCount == 0 {
+            self.global.playing = false;
+            self.global.textAnimate = 0;
+            self.global.textType = Data::text_great;
+            // also repair all ships!
+            // actually this is done by the reset function!
+        }
+    }
+
+    // a synced slot without a ship joined mid match, give it one; a spectator
+    // asking to watch only needs the state, a dropped frame repeats its last
+    // input so only an upload that arrived counts
+    pub fn spawnLateJoiners(&mut self) {
+        self.joined = 0;
+
+        let mut hasShip = [false; Game::SlotCount];
+        for r in self.components.filter(Cf::Player) {
+            let i: usize = r.player.slot.try_into().unwrap();
+            hasShip[i] = true;
+        }
+
+        for (usize_j, slot) in self.slots.iter().enumerate() {
+            if slot.connected == true && hasShip[usize_j] == false && Game::SyncedMask.decode(slot.raw) == 1 {
+                let j: i32 = usize_j.try_into().unwrap();
+                let j16: u16 = usize_j.try_into().unwrap();
+                self.eventList.push( Event::from_player(j16, FixedVector2::from_int(j * 60 - 960 + 32, -500) ) );
+                self.joined |= 1 << usize_j;
+            } else if slot.connected == true && slot.broken == false && hasShip[usize_j] == false && Game::WatchMask.decode(slot.raw) == 1 {
+                self.joined |= 1 << usize_j;
+            }
+        }
+    }
+
+    // whether this slot's ship moves toward its X instead of by keys
+    pub fn steers(steering: Steering, slot: &Slot) -> bool {
+        match steering {
+            Steering::PerPlayer => slot.input.steer,
+            Steering::Keys => false,
+            Steering::Target => true,
+        }
+    }
+
+    pub fn updatePlayers(&mut self) {
+        //Targets.Clear();
+        let mut livePlayer = false;
+
+        for r in self.components.filter(Cf::Active | Cf::Body | Cf::Player) {
+
+            let i: usize = r.player.slot.try_into().unwrap();
+            let slot = self.slots[i];
+
+            r.body.velocity.x = Fixed::Zero;
+            if r.player.delayFire > 0 {
+                r.player.delayFire -= 1;
+            }
+
+            if r.player.damage > 0
+            {
+                // DO NOTHING!
+            }
+            else
+            {
+                if slot.connected == true
+                {
+                    livePlayer = true;
+                }
+                else
+                {
+                    // kill if disconnect!
+                    r.player.damage = 100;
+                    r.animator.frame = Data::_null_persist;
+                    self.eventList.push( Event::from_entity(ObjType::PlayerBoom, r.body.position ) );
+                }
+
+                if r.body.position.x < Fixed::from_int(-960) { r.body.position.x = Fixed::from_int(-960); }
+                if r.body.position.x > Fixed::from_int(960) { r.body.position.x = Fixed::from_int(960); }
+
+                if Game::steers(self.steering, &slot) == true
+                {
+                    let target = Fixed::from_int(i32::from(slot.input.x));
+                    r.body.velocity.x = (target - r.body.position.x).clamp(-Game::MaxSteer, Game::MaxSteer);
+                }
+                else
+                {
+                    if slot.input.left == true { r.body.velocity.x = -Game::MaxSteer; }
+                    if slot.input.right == true { r.body.velocity.x = Game::MaxSteer; }
+                }
+
+                if slot.input.primary && r.player.delayFire == 0
+                {
+                    r.player.delayFire = 24;
+                    self.eventList.push( Event::from_entity(ObjType::Bullet, r.body.position ) );
+                }
+
+            }
+
+        }
+
+        // if all players are damaged, set playing to false, text to no way! and destroy all enemies
+        
+        if self.global.playing == true && livePlayer == false
+        {
+            self.global.playing = false;
+            self.global.textAnimate = 0;
+            self.global.textType = Data::text_no;
+
+            for r in self.components.filter(Cf::Active | Cf::Body | Cf::Enemy) {
+                self.eventList.push( Event::from_destroy(r.entity) );
+            }
+        }
+        
+    }
+    
+
+    pub fn resolveEvents(&mut self) {
+        let mut event_index = 0;
+        while event_index < self.eventList.len() {      
+            match self.eventList[event_index].id {
+                Event::Contact => {
+                    // not using a hash table, and need to get better understanding
+                    // on how nested references work
+                    if self.components.valid(self.eventList[event_index].a) == true && 
+                       self.components.valid(self.eventList[event_index].b) == true {
+                        Game::collisionFunction(self, event_index);
+                    }
+                },
+                Event::DestroyEntity => {
+                    self.components.destroy(self.eventList[event_index].a);
+                },
+                Event::CreateEntity | Event::Shoot => {
+                    // create from prefab!
+                    let entity = self.components.create();
+                    self.data.prefab(self.eventList[event_index].otype).set(&mut self.components, entity);
+                    let mut iter = self.components.pack.iter();
+                    let r = iter.nth(entity).unwrap();
+
+                    if !entity.is_null() {
+                        if r.comp.contains(Cf::Body) == true {
+                            r.body.position = self.eventList[event_index].v;
+                            if self.eventList[event_index].id == Event::Shoot {
+                                r.body.velocity = self.eventList[event_index].d;
+                            }
+                        }
+                        if r.comp.contains(Cf::Player) == true {
+                            r.player.slot = self.eventList[event_index].key.try_into().unwrap();
+                        }
+                        if r.comp.contains(Cf::Enemy) == true {
+                            let count = i32::try_from(Data::enemy_type_count).unwrap();
+                            r.animator.frame = (self.rand.next_from_zero(count) + 2).try_into().unwrap();
+                            r.enemy.delayFire = (self.rand.next_u32() % 2000).try_into().unwrap();
+                        }
+                    }           
+
+                }
+                _ => {
+                    // do nothing, better yet, panic!
+                }
+            }
+            event_index += 1;
+        }
+
+        self.eventList.clear();
+    
+    }
+    
+
+
+}
+
+
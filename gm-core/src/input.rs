@@ -0,0 +1,182 @@
+use std::fmt;
+
+use crate::utility::BitMask;
+
+
+/***************************
+
+    Input Layout
+
+**************************/
+
+// Where every value lives in a raw input.  The lobby and the game each have
+// their own layout, they only need to agree on `synced`, which tells them
+// apart.  Both are checked at compile time for fields that overlap or do not
+// fit in the 56 bits the relay carries.
+
+#[derive(Copy, Clone)]
+pub struct InputField {
+    pub name: &'static str,
+    pub start: usize,
+    pub end: usize,
+    pub mask: BitMask,
+}
+
+impl InputField {
+    pub const fn new(name: &'static str, start: usize, end: usize) -> Self {
+        Self {
+            name,
+            start,
+            end,
+            mask: BitMask::const_from(start, end),
+        }
+    }
+
+    // decodes sign extended
+    pub const fn signed(name: &'static str, start: usize, end: usize) -> Self {
+        Self {
+            name,
+            start,
+            end,
+            mask: BitMask::const_signed(start, end),
+        }
+    }
+
+    pub fn encode(&self, value: i64) -> i64 {
+        self.mask.encode(value)
+    }
+
+    pub fn decode(&self, raw: i64) -> i64 {
+        self.mask.decode(raw)
+    }
+}
+
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LayoutError {
+    // field indices
+    Overlap(usize, usize),
+    OutOfRange(usize),
+    Empty(usize),
+    // the field both layouts need is missing or somewhere else
+    NoSynced,
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LayoutError::Overlap(a, b) => write!(f, "fields {} and {} overlap", a, b),
+            LayoutError::OutOfRange(a) => write!(f, "field {} does not fit in {} bits", a, InputLayout::Bits),
+            LayoutError::Empty(a) => write!(f, "field {} has no bits", a),
+            LayoutError::NoSynced => write!(f, "synced is not at bits {}..{}", InputLayout::Synced.start, InputLayout::Synced.end),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+
+pub struct InputLayout {
+    pub name: &'static str,
+    pub fields: &'static [InputField],
+}
+
+impl InputLayout {
+    // what survives the relay
+    pub const Bits: usize = 56;
+
+    // both
+    pub const Synced: InputField = InputField::new("synced", 55, 56);
+    pub const X: InputField = InputField::signed("x", 0, 16);
+
+    // game
+    pub const Left: InputField = InputField::new("left", 16, 17);
+    pub const Right: InputField = InputField::new("right", 17, 18);
+    pub const Primary: InputField = InputField::new("primary", 18, 19);
+    // desync stamps, see Desync
+    pub const DesyncFlag: InputField = InputField::new("desync_flag", 19, 20);
+    pub const DesyncFrame: InputField = InputField::new("desync_frame", 20, 28);
+    pub const DesyncSum: InputField = InputField::new("desync_sum", 28, 52);
+    // move toward x instead of using left and right, see Steering
+    pub const Steer: InputField = InputField::new("steer", 52, 53);
+    // a spectator joining mid match asks for the state, see LateJoin
+    pub const Watch: InputField = InputField::new("watch", 53, 54);
+
+    // lobby
+    pub const Y: InputField = InputField::signed("y", 16, 32);
+    pub const Seed: InputField = InputField::new("seed", 32, 48);
+    pub const Ready: InputField = InputField::new("ready", 48, 49);
+    // never gets a ship, nobody waits for it to be ready
+    pub const Spectator: InputField = InputField::new("spectator", 49, 50);
+
+    pub const Game: InputLayout = InputLayout {
+        name: "game",
+        fields: &[
+            InputLayout::X,
+            InputLayout::Left,
+            InputLayout::Right,
+            InputLayout::Primary,
+            InputLayout::DesyncFlag,
+            InputLayout::DesyncFrame,
+            InputLayout::DesyncSum,
+            InputLayout::Steer,
+            InputLayout::Watch,
+            InputLayout::Synced,
+        ],
+    };
+
+    pub const Lobby: InputLayout = InputLayout {
+        name: "lobby",
+        fields: &[
+            InputLayout::X,
+            InputLayout::Y,
+            InputLayout::Seed,
+            InputLayout::Ready,
+            InputLayout::Spectator,
+            InputLayout::Synced,
+        ],
+    };
+
+    pub const Layouts: [&'static InputLayout; 2] = [&InputLayout::Lobby, &InputLayout::Game];
+
+    pub fn by_name(name: &str) -> Option<&'static InputLayout> {
+        InputLayout::Layouts.into_iter().find(|layout| layout.name == name)
+    }
+
+    pub fn field(&self, name: &str) -> Option<&'static InputField> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    pub const fn validate(&self) -> Result<(), LayoutError> {
+        let mut synced = false;
+        let mut i = 0;
+        while i < self.fields.len() {
+            let a = &self.fields[i];
+            if a.start >= a.end {
+                return Err(LayoutError::Empty(i));
+            }
+            if a.end > InputLayout::Bits {
+                return Err(LayoutError::OutOfRange(i));
+            }
+            if a.start == InputLayout::Synced.start && a.end == InputLayout::Synced.end {
+                synced = true;
+            }
+            let mut j = i + 1;
+            while j < self.fields.len() {
+                let b = &self.fields[j];
+                if a.start < b.end && b.start < a.end {
+                    return Err(LayoutError::Overlap(i, j));
+                }
+                j += 1;
+            }
+            i += 1;
+        }
+        if !synced {
+            return Err(LayoutError::NoSynced);
+        }
+        Ok(())
+    }
+}
+
+const _: () = assert!(matches!(InputLayout::Game.validate(), Ok(())), "game input layout is invalid");
+const _: () = assert!(matches!(InputLayout::Lobby.validate(), Ok(())), "lobby input layout is invalid");
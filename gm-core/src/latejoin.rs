@@ -0,0 +1,152 @@
+use crate::utility::Slot;
+use crate::game::Game;
+use crate::packet::FramePacket;
+use crate::snapshot::{SnapshotError, SnapshotReader};
+
+
+/***************************
+
+    Late Join
+
+**************************/
+
+// A client joining a running match sends synced inputs like everyone else.
+// The first confirmed frame where its slot is connected and synced spawns its
+// ship on every client (see Game::spawnLateJoiners).  Right after that frame
+// one running client, picked by JoinSnapshot::sender, sends the joiner the
+// state of that frame.  The joiner buffers every frame it receives until the
+// snapshot arrives, adopts it, then replays the frames that came after.
+// A spectator never syncs; instead it sets `watch` on one upload, and the
+// frame that upload arrives on is its join frame.
+//
+// Relay packets carry no frame number, so the snapshot names its frame and
+// brings the packet that frame was simulated with; the joiner looks for that
+// packet among the ones it buffered instead of guessing from its own inputs.
+// Only gm-relay passes snapshots on, so give up after LateJoin::Timeout frames.
+
+// "GMJN", u64 joined, u32 frame, the join frame's packet, then the snapshot
+
+pub struct JoinSnapshot {
+    // slots that were spawned on the snapshot's frame
+    pub joined: u64,
+    // Game::frame of the state
+    pub frame: u32,
+    // what the relay sent for that frame
+    pub packet: FramePacket,
+    pub state: Vec<u8>,
+}
+
+impl JoinSnapshot {
+    pub const MAGIC: [u8; 4] = *b"GMJN";
+    pub const HeaderSize: usize = 16 + FramePacket::Size;
+
+    // right after the update that set `joined`, the slots still hold its inputs
+    pub fn from_game(game: &Game) -> Self {
+        Self {
+            joined: game.joined,
+            frame: game.frame,
+            packet: FramePacket::from_slots(&game.slots),
+            state: game.save_snapshot(),
+        }
+    }
+
+    // the lowest synced slot that was already playing sends the snapshot
+    pub fn sender(game: &Game) -> Option<usize> {
+        (0..game.slots.len()).find(|&i| {
+            game.joined & (1 << i) == 0 &&
+            game.slots[i].connected &&
+            Game::SyncedMask.decode(game.slots[i].raw) == 1
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut ret = Vec::with_capacity(JoinSnapshot::HeaderSize + self.state.len());
+        ret.extend_from_slice(&JoinSnapshot::MAGIC);
+        ret.extend_from_slice(&self.joined.to_le_bytes());
+        ret.extend_from_slice(&self.frame.to_le_bytes());
+        ret.extend_from_slice(&self.packet.encode());
+        ret.extend_from_slice(&self.state);
+        ret
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        if bytes.len() < 4 || bytes[0..4] != JoinSnapshot::MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        if bytes.len() < JoinSnapshot::HeaderSize {
+            return Err(SnapshotError::Truncated);
+        }
+        let state = &bytes[JoinSnapshot::HeaderSize..];
+        // check the embedded snapshot's header now rather than after buffering
+        SnapshotReader::new(state)?;
+        Ok(Self {
+            joined: u64::from_le_bytes(bytes[4..12].try_into().unwrap()),
+            frame: u32::from_le_bytes(bytes[12..16].try_into().unwrap()),
+            // the length is always right, so this cannot fail
+            packet: FramePacket::decode(&bytes[16..JoinSnapshot::HeaderSize]).unwrap(),
+            state: state.to_vec(),
+        })
+    }
+}
+
+
+pub struct LateJoin {
+    slot: usize,
+    // only what the relay sent, the snapshot has everything else
+    pending: Vec<FramePacket>,
+}
+
+impl LateJoin {
+    // frames to wait for a snapshot, about ten seconds
+    pub const Timeout: usize = 300;
+
+    pub fn new(slot: usize) -> Self {
+        Self {
+            slot,
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn slot(&self) -> usize {
+        self.slot
+    }
+
+    // nobody sent the state in time, most likely the relay does not forward it
+    pub fn timed_out(&self) -> bool {
+        self.pending.len() > LateJoin::Timeout
+    }
+
+    // call with every confirmed frame received while waiting for the snapshot
+    pub fn buffer(&mut self, slots: &[Slot]) {
+        self.pending.push(FramePacket::from_slots(slots));
+    }
+
+    // Ok(false) means our join frame has not arrived yet, keep the packet and try again
+    pub fn adopt(&mut self, game: &mut Game, packet: &[u8]) -> Result<bool, SnapshotError> {
+        let snapshot = JoinSnapshot::decode(packet)?;
+        if snapshot.joined & (1 << self.slot) == 0 {
+            return Err(SnapshotError::Invalid("snapshot is for another slot"));
+        }
+
+        // every frame before ours has our slot neither synced nor watching,
+        // so the first identical packet is the one
+        let join_frame = match self.pending.iter().position(|frame| *frame == snapshot.packet) {
+            Some(i) => i,
+            None => return Ok(false),
+        };
+
+        let mut loaded = Game::with_data(game.data.clone());
+        loaded.load_snapshot(&snapshot.state)?;
+        if loaded.frame != snapshot.frame {
+            return Err(SnapshotError::Invalid("snapshot is not of its join frame"));
+        }
+        game.smartCopy(&loaded);
+        game.desync.reset();
+
+        for frame in self.pending.drain(..).skip(join_frame + 1) {
+            frame.apply(&mut game.slots);
+            game.update();
+        }
+        Ok(true)
+    }
+}
@@ -0,0 +1,80 @@
+/*******************************************
+
+    Galactic Marauders deterministic core
+
+    Everything needed to run the simulation
+    without Godot.  The GDNative library is
+    just a thin adapter over this crate.
+
+*******************************************/
+
+// The simulation keeps the naming it was written with in the GDNative library.
+#![allow(non_snake_case, non_upper_case_globals)]
+
+pub mod containers;
+pub mod twister;
+pub mod vector;
+pub mod fixed;
+pub mod trig;
+pub mod entity;
+pub mod components;
+// ported as they were, new modules get linted normally
+#[allow(clippy::bool_comparison, clippy::redundant_field_names)]
+pub mod utility;
+pub mod input;
+#[allow(
+    clippy::bool_comparison,
+    clippy::partialeq_to_none,
+    clippy::needless_return,
+    clippy::needless_borrow,
+    clippy::needless_late_init,
+    clippy::redundant_field_names,
+    clippy::assign_op_pattern,
+)]
+pub mod pools;
+#[allow(clippy::useless_conversion)]
+pub mod data;
+pub mod broadphase;
+#[allow(
+    clippy::bool_comparison,
+    clippy::partialeq_to_none,
+    clippy::needless_borrow,
+    clippy::collapsible_match,
+    clippy::useless_conversion,
+    clippy::unnecessary_fallible_conversions,
+)]
+pub mod game;
+pub mod checksum;
+pub mod desync;
+pub mod snapshot;
+pub mod latejoin;
+pub mod rollback;
+pub mod prediction;
+pub mod packet;
+pub mod relay;
+pub mod netsim;
+pub mod replay;
+
+pub use containers::IndexTable;
+pub use twister::MersenneTwister;
+pub use vector::Vector2;
+pub use fixed::{Fixed, FixedVector2};
+pub use trig::Angle;
+pub use entity::Entity;
+pub use components::{Animator, Body, Player, Enemy, ObjType, Cf};
+pub use utility::{BitMask, Control, Slot, GlobalState, Bounds, CollisionMatrix, Event};
+pub use input::{InputField, InputLayout, LayoutError};
+pub use pools::{CpPack, CpReference, CpIterMut, CpPrefab, Cp};
+pub use data::{Data, CollisionHandler};
+pub use broadphase::Broadphase;
+pub use game::{Game, PredictionMode, Steering, BrokenInput};
+pub use checksum::{Checksum, StateHash};
+pub use desync::Desync;
+pub use snapshot::{Snapshot, SnapshotError, SnapshotReader, SnapshotWriter};
+pub use latejoin::{JoinSnapshot, LateJoin};
+pub use rollback::{RollbackFrame, RollbackSession};
+pub use packet::{FramePacket, PacketError};
+pub use prediction::{Predictor, RepeatLast, Neutral, ReleaseFire, PredictionStats, predictor_from_id, predict_remote};
+pub use relay::RelayRoom;
+pub use netsim::{NetConditions, NetSim, VirtualClient};
+pub use replay::{Replay, ReplayError, ReplayPlayer};
@@ -0,0 +1,264 @@
+use std::collections::VecDeque;
+
+use crate::twister::MersenneTwister;
+use crate::game::Game;
+use crate::relay::RelayRoom;
+use crate::prediction::{RepeatLast, predict_remote};
+
+
+/***************************
+
+    Network Simulation
+
+**************************/
+
+// Runs several clients against an in-memory relay, one millisecond at a time.
+// Every client does what Main.gd does with its Game pair: upload at most
+// MaxForward inputs ahead, update the confirmed game on every frame packet,
+// then copy it and fast forward through its own unconfirmed inputs.  After
+// every update the confirmed checksum is recorded so the clients can be
+// compared frame by frame.
+
+// Everything is in milliseconds or percent.
+#[derive(Default, Copy, Clone)]
+pub struct NetConditions {
+    // one way delay, both directions
+    pub latency: u32,
+    // up to this much extra delay per message, order is still kept
+    pub jitter: u32,
+    // uploads that never reach the relay
+    pub loss: u32,
+    // relay ticks where this slot's input is held back and it is marked dropped
+    pub dropped: u32,
+}
+
+impl NetConditions {
+    pub fn perfect() -> Self {
+        Default::default()
+    }
+}
+
+
+pub struct VirtualClient {
+    pub slot: usize,
+    pub conditions: NetConditions,
+    // confirmed game, and the copy that runs ahead of it
+    pub game: Game,
+    pub forward: Game,
+    // confirmed checksum after every frame, index 0 is frame 1
+    pub checksums: Vec<u64>,
+    started: bool,
+    // Main.gd's WaitingOnFirst, until our own input comes back for the first time
+    waiting: bool,
+    uploads: VecDeque<i64>,
+    frameSkip: u32,
+    // messages on the wire with their arrival time
+    incoming: VecDeque<(u32, Vec<u8>)>,
+    outgoing: VecDeque<(u32, i64)>,
+    // the keys this client is pretending to hold, and for how long
+    held: i64,
+    holdFor: u32,
+    rand: MersenneTwister,
+}
+
+impl VirtualClient {
+
+    fn new(slot: usize, conditions: NetConditions, seed: u32) -> Self {
+        Self {
+            slot,
+            conditions,
+            game: Game::new(),
+            forward: Game::new(),
+            checksums: Vec::new(),
+            started: false,
+            waiting: true,
+            uploads: VecDeque::new(),
+            frameSkip: 0,
+            incoming: VecDeque::new(),
+            outgoing: VecDeque::new(),
+            held: 0,
+            holdFor: 0,
+            rand: MersenneTwister::from(seed),
+        }
+    }
+
+    fn chance(&mut self, percent: u32) -> bool {
+        percent > 0 && self.rand.next_u32() % 100 < percent
+    }
+
+    // keeps the same order as an earlier message would, like a websocket
+    fn arrival(&mut self, now: u32, last: Option<u32>) -> u32 {
+        let jitter = if self.conditions.jitter > 0 { self.rand.next_u32() % (self.conditions.jitter + 1) } else { 0 };
+        let at = now + self.conditions.latency + jitter;
+        at.max(last.unwrap_or(0))
+    }
+
+    // random key presses, held for a few frames like a person would
+    fn next_input(&mut self) -> i64 {
+        if self.holdFor == 0 {
+            let keys = self.rand.next_u32();
+            self.held = Game::LeftMask.encode(i64::from(keys & 1))
+                | Game::RightMask.encode(i64::from((keys >> 1) & 1))
+                | Game::PrimaryMask.encode(i64::from((keys >> 2) & 1));
+            self.holdFor = 1 + (keys >> 3) % 20;
+        }
+        self.holdFor -= 1;
+        self.game.desync.stamp(self.held | Game::SyncedMask.encode(1))
+    }
+
+    fn upload(&mut self, now: u32) {
+        let input = self.next_input();
+        if self.frameSkip > 0 {
+            self.frameSkip -= 1;
+            return;
+        }
+        if self.uploads.len() >= NetSim::MaxForward {
+            return;
+        }
+        self.uploads.push_back(input);
+        let lost = self.chance(self.conditions.loss);
+        let at = self.arrival(now, self.outgoing.back().map(|m| m.0));
+        if !lost {
+            self.outgoing.push_back((at, input));
+        }
+    }
+
+    fn send_packet(&mut self, now: u32, bytes: Vec<u8>) {
+        let at = self.arrival(now, self.incoming.back().map(|m| m.0));
+        self.incoming.push_back((at, bytes));
+    }
+
+    fn receive(&mut self, seed: u32, bytes: &[u8]) {
+        self.game.read_packet(bytes).unwrap();
+        if !self.started {
+            // everyone joined before the first tick, so this is everyone's start
+            for slot in &mut self.game.slots {
+                slot.connectedAtStart = slot.connected;
+            }
+            self.game.init(seed);
+            self.started = true;
+        }
+
+        let broken = self.game.slots[self.slot].broken;
+        if self.waiting && !broken {
+            self.waiting = false;
+        }
+        if !self.waiting {
+            if !broken {
+                self.uploads.pop_front();
+            } else {
+                self.frameSkip += 1;
+            }
+        }
+
+        if !self.game.gameOver {
+            self.game.update();
+            self.checksums.push(self.game.checksum());
+        }
+
+        self.forward.smartCopy(&self.game);
+        let ahead = self.uploads.len().saturating_sub(NetSim::MaxDelay);
+        let last = self.game.slots.clone();
+        for (n, raw) in self.uploads.iter().take(ahead).enumerate() {
+            predict_remote(&RepeatLast, &mut self.forward.slots, &last, self.slot, (n + 1).try_into().unwrap());
+            self.forward.slots[self.slot].raw = *raw;
+            self.forward.slots[self.slot].broken = false;
+            self.forward.fastForward();
+        }
+    }
+}
+
+
+pub struct NetSim {
+    pub relay: RelayRoom,
+    pub clients: Vec<VirtualClient>,
+    // milliseconds since the start
+    pub time: u32,
+    seed: u32,
+    rand: MersenneTwister,
+}
+
+impl NetSim {
+    // same pacing as Main.gd
+    pub const TickLength: u32 = 33;
+    pub const MaxForward: usize = 4;
+    pub const MaxDelay: usize = 1;
+
+    // one client per entry, joined in order so client n gets slot n
+    pub fn new(seed: u32, conditions: &[NetConditions]) -> Self {
+        let mut relay = RelayRoom::new();
+        let clients = conditions.iter().enumerate().map(|(n, c)| {
+            let slot = relay.join().expect("more clients than slots");
+            let salt: u32 = n.try_into().unwrap();
+            VirtualClient::new(usize::from(slot), *c, seed ^ (0x9e37_79b9_u32.wrapping_mul(salt + 1)))
+        }).collect();
+        Self {
+            relay,
+            clients,
+            time: 0,
+            seed,
+            rand: MersenneTwister::from(seed),
+        }
+    }
+
+    pub fn run(&mut self, milliseconds: u32) {
+        for _ in 0..milliseconds {
+            self.step();
+        }
+    }
+
+    pub fn step(&mut self) {
+        let now = self.time;
+
+        // clients upload on their own clock, a little out of phase with the relay
+        for (n, client) in self.clients.iter_mut().enumerate() {
+            let phase: u32 = (n * 7).try_into().unwrap();
+            if (now + phase).is_multiple_of(NetSim::TickLength) {
+                client.upload(now);
+            }
+        }
+
+        for client in &mut self.clients {
+            while client.outgoing.front().is_some_and(|m| m.0 <= now) {
+                let (_, raw) = client.outgoing.pop_front().unwrap();
+                let slot: u8 = client.slot.try_into().unwrap();
+                self.relay.upload(slot, raw);
+            }
+        }
+
+        if now.is_multiple_of(NetSim::TickLength) {
+            let mut held = 0u64;
+            for client in &self.clients {
+                if client.conditions.dropped > 0 && self.rand.next_u32() % 100 < client.conditions.dropped {
+                    held |= 1 << client.slot;
+                }
+            }
+            let bytes = self.relay.tick_holding(held).encode();
+            for client in &mut self.clients {
+                client.send_packet(now, bytes.clone());
+            }
+        }
+
+        for client in &mut self.clients {
+            while client.incoming.front().is_some_and(|m| m.0 <= now) {
+                let (_, bytes) = client.incoming.pop_front().unwrap();
+                client.receive(self.seed, &bytes);
+            }
+        }
+
+        self.time += 1;
+    }
+
+    // frames every client has confirmed so far
+    pub fn confirmed_frames(&self) -> usize {
+        self.clients.iter().map(|c| c.checksums.len()).min().unwrap_or(0)
+    }
+
+    // first frame where some client's confirmed checksum differs from client 0's
+    pub fn first_mismatch(&self) -> Option<u32> {
+        let first = self.clients.first()?;
+        (0..self.confirmed_frames())
+            .find(|&f| self.clients.iter().any(|c| c.checksums[f] != first.checksums[f]))
+            .map(|f| u32::try_from(f).unwrap() + 1)
+    }
+}
@@ -0,0 +1,124 @@
+use std::fmt;
+
+use crate::utility::Slot;
+use crate::game::Game;
+
+
+/***************************
+
+    Frame Packet
+
+**************************/
+
+// What the relay server broadcasts every tick: an 8 byte little endian
+// connected bitmask, an 8 byte dropped bitmask, then 7 little endian bytes of
+// raw input for every slot.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PacketError {
+    WrongLength { found: usize, expected: usize },
+}
+
+impl fmt::Display for PacketError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PacketError::WrongLength { found, expected } =>
+                write!(f, "frame packet is {} bytes, expected {}", found, expected),
+        }
+    }
+}
+
+impl std::error::Error for PacketError {}
+
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct FramePacket {
+    pub connected: u64,
+    pub dropped: u64,
+    pub raw: [i64; Game::SlotCount],
+}
+
+impl FramePacket {
+    pub const HeaderSize: usize = 16;
+    pub const InputSize: usize = 7;
+    pub const Size: usize = FramePacket::HeaderSize + FramePacket::InputSize * Game::SlotCount;
+
+    // only the low 56 bits of an input make it through the relay
+    pub const InputMask: i64 = 0x00ffffffffffffff;
+
+    pub fn new() -> Self {
+        Self {
+            connected: 0,
+            dropped: 0,
+            raw: [0; Game::SlotCount],
+        }
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, PacketError> {
+        if bytes.len() != FramePacket::Size {
+            return Err(PacketError::WrongLength { found: bytes.len(), expected: FramePacket::Size });
+        }
+        let mut packet = FramePacket::new();
+        packet.connected = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+        packet.dropped = u64::from_le_bytes(bytes[8..16].try_into().unwrap());
+        for (n, raw) in packet.raw.iter_mut().enumerate() {
+            let offset = FramePacket::HeaderSize + n * FramePacket::InputSize;
+            *raw = FramePacket::decode_input(&bytes[offset..offset + FramePacket::InputSize])?;
+        }
+        Ok(packet)
+    }
+
+    // what a client uploads every tick: the low 7 bytes of its raw input
+    pub fn decode_input(bytes: &[u8]) -> Result<i64, PacketError> {
+        if bytes.len() != FramePacket::InputSize {
+            return Err(PacketError::WrongLength { found: bytes.len(), expected: FramePacket::InputSize });
+        }
+        let mut buffer = [0u8; 8];
+        buffer[..FramePacket::InputSize].copy_from_slice(bytes);
+        Ok(i64::from_le_bytes(buffer))
+    }
+
+    pub fn encode_input(raw: i64) -> Vec<u8> {
+        raw.to_le_bytes()[..FramePacket::InputSize].to_vec()
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(FramePacket::Size);
+        bytes.extend_from_slice(&self.connected.to_le_bytes());
+        bytes.extend_from_slice(&self.dropped.to_le_bytes());
+        for raw in &self.raw {
+            bytes.extend_from_slice(&raw.to_le_bytes()[..FramePacket::InputSize]);
+        }
+        bytes
+    }
+
+    // the packet that would have produced these slots
+    pub fn from_slots(slots: &[Slot]) -> Self {
+        let mut packet = FramePacket::new();
+        for (n, slot) in slots.iter().enumerate().take(Game::SlotCount) {
+            if slot.connected {
+                packet.connected |= 1 << n;
+            }
+            if slot.broken {
+                packet.dropped |= 1 << n;
+            }
+            packet.raw[n] = slot.raw & FramePacket::InputMask;
+        }
+        packet
+    }
+
+    // connectedAtStart is decided in the lobby, so it is left alone
+    pub fn apply(&self, slots: &mut [Slot]) {
+        for (n, slot) in slots.iter_mut().enumerate().take(Game::SlotCount) {
+            slot.connected = self.connected & (1 << n) != 0;
+            slot.broken = self.dropped & (1 << n) != 0;
+            slot.raw = self.raw[n];
+        }
+    }
+}
+
+impl Default for FramePacket {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,496 @@
+use std::slice::IterMut;
+use std::iter::Enumerate;
+
+use crate::containers::IndexTable;
+use crate::entity::Entity;
+use crate::components::*;
+
+
+/***************************
+
+    Component Pools
+
+**************************/
+
+#[derive(Default)]
+pub struct CpPack {
+    pub generation: Vec<u16>,
+    pub comp: Vec<Cf>,
+    pub objectId: Vec<ObjType>,
+    pub body: Vec<Body>,
+    pub player: Vec<Player>,
+    pub enemy: Vec<Enemy>,
+    pub animator: Vec<Animator>,
+}
+
+impl CpPack {
+
+    pub fn new() -> CpPack { 
+        Default::default()
+    }
+
+    /*fn group(&self) -> [&dyn CpContainer; 7] {
+        [
+            &self.generation,
+            &self.comp,
+            &self.objectId,
+            &self.body,
+            &self.player,
+            &self.enemy,
+            &self.animator,
+        ]
+    }
+
+    fn group_mut(&mut self) -> [&mut dyn CpContainer; 7] {
+        [
+            &mut self.generation,
+            &mut self.comp,
+            &mut self.objectId,
+            &mut self.body,
+            &mut self.player,
+            &mut self.enemy,
+            &mut self.animator,
+        ]
+    }*/
+
+
+    pub fn iter(&mut self) -> CpIterMut<'_> {
+        CpIterMut {
+            mask: 0,
+            generation: self.generation.iter_mut().enumerate(),
+            comp: self.comp.iter_mut().enumerate(),
+            objectId: self.objectId.iter_mut().enumerate(),
+            body: self.body.iter_mut().enumerate(),
+            player: self.player.iter_mut().enumerate(),
+            enemy: self.enemy.iter_mut().enumerate(),
+            animator: self.animator.iter_mut().enumerate(),
+        }
+    }
+
+    pub fn filter(&mut self, mask: u8) -> CpIterMut<'_> {
+        CpIterMut {
+            mask: mask,
+            generation: self.generation.iter_mut().enumerate(),
+            comp: self.comp.iter_mut().enumerate(),
+            objectId: self.objectId.iter_mut().enumerate(),
+            body: self.body.iter_mut().enumerate(),
+            player: self.player.iter_mut().enumerate(),
+            enemy: self.enemy.iter_mut().enumerate(),
+            animator: self.animator.iter_mut().enumerate(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.generation.clear();
+        self.comp.clear();
+        self.objectId.clear();
+        self.body.clear();
+        self.player.clear();
+        self.enemy.clear();
+        self.animator.clear();
+    }
+
+    pub fn size(&self) -> usize {
+        self.generation.len()
+    }
+
+    pub fn resize(&mut self, s: usize) {
+        if self.size() < s {
+            self.generation.resize(s, Default::default());
+            self.comp.resize(s, Default::default());
+            self.objectId.resize(s, Default::default());
+            self.body.resize(s, Default::default());
+            self.player.resize(s, Default::default());
+            self.enemy.resize(s, Default::default());
+            self.animator.resize(s, Default::default());
+        }
+    }
+
+    pub fn smartCopy(&mut self, other: &CpPack) {
+        self.generation.resize(other.generation.len(), Default::default());        
+        self.comp.resize(other.comp.len(), Default::default());
+        self.objectId.resize(other.objectId.len(), Default::default());
+        self.body.resize(other.body.len(), Default::default());
+        self.player.resize(other.player.len(), Default::default());
+        self.enemy.resize(other.enemy.len(), Default::default());
+        self.animator.resize(other.animator.len(), Default::default());
+
+        self.generation.as_mut_slice().copy_from_slice(&other.generation.as_slice());
+        self.comp.as_mut_slice().copy_from_slice(&other.comp.as_slice());
+        self.objectId.as_mut_slice().copy_from_slice(&other.objectId.as_slice());
+        self.body.as_mut_slice().copy_from_slice(&other.body.as_slice());
+        self.player.as_mut_slice().copy_from_slice(&other.player.as_slice());
+        self.enemy.as_mut_slice().copy_from_slice(&other.enemy.as_slice());
+        self.animator.as_mut_slice().copy_from_slice(&other.animator.as_slice());
+    }
+
+}
+
+pub struct CpReference<'a> {
+    pub entity: Entity,
+    pub comp: &'a mut Cf,
+    pub objectId: &'a mut ObjType,
+    pub body: &'a mut Body,
+    pub player: &'a mut Player,
+    pub enemy: &'a mut Enemy,
+    pub animator: &'a mut Animator,
+}
+
+pub struct CpIterMut<'a> {
+    pub mask: u8,
+    pub generation: Enumerate<IterMut<'a, u16>>,
+    pub comp: Enumerate<IterMut<'a, Cf>>,
+    pub objectId: Enumerate<IterMut<'a, ObjType>>,
+    pub body: Enumerate<IterMut<'a, Body>>,
+    pub player: Enumerate<IterMut<'a, Player>>,
+    pub enemy: Enumerate<IterMut<'a, Enemy>>,
+    pub animator: Enumerate<IterMut<'a, Animator>>,
+}
+
+impl<'a> CpIterMut<'a> {
+
+    pub fn nth(&mut self, e: Entity) -> Option<CpReference<'_>> {
+        let n = e.index();
+        let generation = self.generation.nth(n);
+        let comp = self.comp.nth(n);
+        let objectId = self.objectId.nth(n);
+        let body = self.body.nth(n);
+        let player = self.player.nth(n);
+        let enemy = self.enemy.nth(n);
+        let animator = self.animator.nth(n);
+        if generation == None {
+            None
+        } else {
+            let g = generation.unwrap();
+            Some(
+                CpReference {
+                    entity: Entity::from(g.0.try_into().unwrap(), *g.1),
+                    comp: comp.unwrap().1,
+                    objectId: objectId.unwrap().1,
+                    body: body.unwrap().1,
+                    player: player.unwrap().1,
+                    enemy: enemy.unwrap().1,
+                    animator: animator.unwrap().1,
+                }
+            )
+        }
+    }
+
+    pub fn nth_double(&mut self, a: Entity, b: Entity) -> Option<(CpReference<'_>, CpReference<'_>)> {
+    
+        let ia = a.index();
+        let ib = b.index();
+
+        if ia == ib {
+            return None;
+        }
+
+        let swapped = ia > ib;
+
+        let mut n0 = ia;
+        let mut n1 = ib;
+
+        if swapped == true {
+            n0 = ib;
+            n1 = ia;
+        }
+
+        n1 = n1 - (n0 + 1);
+
+        let A: CpReference;
+        let B: CpReference;
+
+        let generation = self.generation.nth(n0);
+        let comp = self.comp.nth(n0);
+        let objectId = self.objectId.nth(n0);
+        let body = self.body.nth(n0);
+        let player = self.player.nth(n0);
+        let enemy = self.enemy.nth(n0);
+        let animator = self.animator.nth(n0);
+        
+        if generation == None {
+            return None;
+        } else {
+            let g = generation.unwrap();
+            A = CpReference {
+                entity: Entity::from(g.0.try_into().unwrap(), *g.1),
+                comp: comp.unwrap().1,
+                objectId: objectId.unwrap().1,
+                body: body.unwrap().1,
+                player: player.unwrap().1,
+                enemy: enemy.unwrap().1,
+                animator: animator.unwrap().1,
+            };
+        }
+
+        let generation = self.generation.nth(n1);
+        let comp = self.comp.nth(n1);
+        let objectId = self.objectId.nth(n1);
+        let body = self.body.nth(n1);
+        let player = self.player.nth(n1);
+        let enemy = self.enemy.nth(n1);
+        let animator = self.animator.nth(n1);
+        
+        if generation == None {
+            return None;
+        } else {
+            let g = generation.unwrap();
+            B = CpReference {
+                entity: Entity::from(g.0.try_into().unwrap(), *g.1),
+                comp: comp.unwrap().1,
+                objectId: objectId.unwrap().1,
+                body: body.unwrap().1,
+                player: player.unwrap().1,
+                enemy: enemy.unwrap().1,
+                animator: animator.unwrap().1,
+            };
+        }
+
+        if swapped == true {
+            return Some((B, A));
+        } else {
+            return Some((A, B));
+        }
+
+    }
+
+}
+
+impl<'a> Iterator for CpIterMut<'a> {
+    type Item = CpReference<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let generation = self.generation.next();
+            let comp = self.comp.next();
+            let objectId = self.objectId.next();
+            let body = self.body.next();
+            let player = self.player.next();
+            let enemy = self.enemy.next();
+            let animator = self.animator.next();
+            if generation == None {
+                return None;
+            } else {
+                let c = comp.unwrap();
+                if c.1.contains(self.mask) {
+                    return Some(
+                        CpReference {
+                            entity: Entity::from(c.0.try_into().unwrap(), *generation.unwrap().1),
+                            comp: c.1,
+                            objectId: objectId.unwrap().1,
+                            body: body.unwrap().1,
+                            player: player.unwrap().1,
+                            enemy: enemy.unwrap().1,
+                            animator: animator.unwrap().1,
+                        }
+                    );
+                } else {
+                    // do nothing, keep going!
+                }
+            }
+        }
+    }
+}
+
+
+#[derive(Default, Copy, Clone)]
+pub struct CpPrefab {
+    pub comp: Cf,
+    pub objectId: ObjType,
+    pub body: Body,
+    pub player: Player,
+    pub enemy: Enemy,
+    pub animator: Animator,
+}
+
+impl CpPrefab {
+
+    pub fn get(&mut self, cp: &Cp, entity: Entity) {
+        let i =  entity.index();
+        if cp.pack.comp[i].contains(Cf::Component) == true { self.comp = cp.pack.comp[i]; }
+        if cp.pack.comp[i].contains(Cf::ObjectId) == true {  self.objectId = cp.pack.objectId[i]; }
+        if cp.pack.comp[i].contains(Cf::Body) == true { self.body = cp.pack.body[i]; }
+        if cp.pack.comp[i].contains(Cf::Player) == true { self.player = cp.pack.player[i]; }
+        if cp.pack.comp[i].contains(Cf::Enemy) == true { self.enemy = cp.pack.enemy[i]; }
+        if cp.pack.comp[i].contains(Cf::Animator) == true { self.animator = cp.pack.animator[i]; }
+    }
+
+    pub fn set(&self, cp: &mut Cp, entity: Entity) {
+        if cp.valid(entity) == true {
+            let i =  entity.index();
+            if self.comp.contains(Cf::Component) == true { cp.pack.comp[i] = self.comp; }
+            if self.comp.contains(Cf::ObjectId) == true { cp.pack.objectId[i] = self.objectId; }
+            if self.comp.contains(Cf::Body) == true { cp.pack.body[i] = self.body; }
+            if self.comp.contains(Cf::Player) == true { cp.pack.player[i] = self.player; }
+            if self.comp.contains(Cf::Enemy) == true { cp.pack.enemy[i] = self.enemy; }
+            if self.comp.contains(Cf::Animator) == true { cp.pack.animator[i] = self.animator; }
+        }
+    }
+
+}
+
+
+
+#[derive(Default)]
+pub struct Cp {
+    pub manager: IndexTable,
+    pub pack: CpPack,
+}
+
+
+impl Cp {
+
+    pub fn new() -> Self {
+        let mut cp:Cp = Default::default();
+        cp.clear();
+        cp
+    }
+
+    pub fn smartCopy(&mut self, other: &Self) {
+        self.manager.list.resize(other.manager.list.len(), Default::default());   
+        self.manager.head = other.manager.head;
+        self.manager.list.as_mut_slice().copy_from_slice(&other.manager.list.as_slice());
+        self.pack.smartCopy(&other.pack);
+    }
+
+    pub fn valid(&self, entity: Entity) -> bool {
+        !entity.is_null() && entity.generation() == self.pack.generation[entity.index()]
+    }
+
+    pub fn destroy(&mut self, entity: Entity) {
+        if self.valid(entity) == true {
+            self.pack.generation[entity.index()] += 1;
+            self.pack.comp[entity.index()].0 = Cf::None;
+
+            self.manager.free( entity.id() );
+        }
+    }
+
+    pub fn create(&mut self) -> Entity {
+        let mut entity = Entity::new();
+        let value = self.manager.allocate();
+        if value != IndexTable::END_OF_LIST {
+            let deref:usize = value.into();
+            self.pack.resize(deref + 1);
+            self.pack.comp[deref].0 = Cf::None;
+            entity = Entity::from(value, self.pack.generation[deref])
+        }
+        entity
+    }
+
+    pub fn clear(&mut self) {
+        self.manager.reset();
+        self.pack.clear();
+    }
+
+    pub fn iter(&mut self) -> CpIterMut<'_> {
+        self.pack.iter()
+    }
+
+    pub fn filter(&mut self, mask: u8) -> CpIterMut<'_> {
+        self.pack.filter(mask)
+    }
+
+    pub fn print(&self, index: u16) -> bool {
+        let mut ret = false;
+        let i: usize = index.into();
+        if i < self.pack.comp.len() {
+            println!("*********************************************");
+            println!("Entity Index: {}", index);
+            println!("Comp: {}", self.pack.comp[i].0);
+            println!("ObjectId: {}", self.pack.objectId[i].0);
+            println!("body position x: {}", self.pack.body[i].position.x);
+            println!("body position y: {}", self.pack.body[i].position.y);
+            println!("Enemy direction: {}", self.pack.enemy[i].direction);
+            println!("Enemy counter: {}", self.pack.enemy[i].counter);
+            println!("Enemy delayFire: {}", self.pack.enemy[i].delayFire);
+            println!("Animator frame: {}", self.pack.animator[i].frame);
+            println!("Animator counter: {}", self.pack.animator[i].count);
+
+            if self.pack.comp[i].none() == true {
+                ret = true;
+            }
+        }
+        ret
+    }
+
+
+
+}
+
+
+/***************************
+
+    Component Iterators
+
+**************************/
+
+
+/*
+struct SliceComp<'a> {
+    comp: &'a [Cf],
+}
+
+struct SliceMutBodyEnemy<'a> {
+    body: &'a mut [Body],
+    enemy: &'a mut [Enemy],
+}
+
+struct PackCompMutBodyEnemy<'a> {
+    pack: SliceComp<'a>,
+    pack_mut: SliceMutBodyEnemy<'a>,
+}
+
+
+impl<'a> PackCompMutBodyEnemy<'a> {
+    fn iter_mut(self) -> IterMutBodyEnemy<'a> {
+        IterMutBodyEnemy {
+            comp: self.comp.iter().enumerate(),
+            body: self.body.iter_mut().enumerate(),
+            enemy: self.enemy.iter_mut().enumerate(),
+        }
+    }
+}
+
+struct IterCompMutBodyEnemy<'a> {
+    comp: Enumerate<Iter<'a, Cf>>,
+    body: Enumerate<IterMut<'a, Body>>,
+    enemy: Enumerate<IterMut<'a, Enemy>>,
+}
+
+struct RefMutBodyEnemy<'a> {
+    body: &'a mut Body,
+    enemy: &'a mut Enemy,
+}
+
+impl<'a> Iterator for IterMutBodyEnemy<'a> {
+    type Item = RefMutBodyEnemy<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut done = false;
+        while done == false {
+            let comp = self.comp.next();
+            let body = self.body.next();
+            let enemy = self.enemy.next();
+            if comp == None {
+                done = true;
+                return None;
+            } else {
+                if comp.unwrap().1.contains(Cf::Active | Cf::Body | Cf::Enemy) {
+                    done = true;
+                    return Some(
+                        RefMutBodyEnemy {
+                            body: body.unwrap().1,
+                            enemy: enemy.unwrap().1,
+                        }
+                    );
+                } else {
+                    // do nothing, keep going!
+                }
+            }
+        }
+        None
+    }
+
+}
+*/
@@ -0,0 +1,124 @@
+use crate::utility::Slot;
+use crate::game::Game;
+use crate::desync::Desync;
+
+
+/***************************
+
+    Prediction
+
+**************************/
+
+// How a remote slot's input is guessed for frames the server has not
+// confirmed yet.  `ahead` is how many frames past the slot's last confirmed
+// input the guess is for, starting at 1.
+pub trait Predictor {
+    fn predict(&self, last: &Slot, ahead: u32) -> i64;
+}
+
+// keep doing whatever the last confirmed frame did
+#[derive(Default, Copy, Clone)]
+pub struct RepeatLast;
+
+impl Predictor for RepeatLast {
+    fn predict(&self, last: &Slot, _ahead: u32) -> i64 {
+        last.raw
+    }
+}
+
+// assume nothing is pressed, but stay in game
+#[derive(Default, Copy, Clone)]
+pub struct Neutral;
+
+impl Predictor for Neutral {
+    fn predict(&self, last: &Slot, _ahead: u32) -> i64 {
+        last.raw & Game::SyncedMask.mask
+    }
+}
+
+// repeat the last input, but let go of fire after a few frames
+#[derive(Default, Copy, Clone)]
+pub struct ReleaseFire {
+    pub frames: u32,
+}
+
+impl Predictor for ReleaseFire {
+    fn predict(&self, last: &Slot, ahead: u32) -> i64 {
+        if ahead > self.frames {
+            last.raw & !Game::PrimaryMask.mask
+        } else {
+            last.raw
+        }
+    }
+}
+
+// so a strategy can be picked by number from Godot
+pub fn predictor_from_id(id: i64, param: i64) -> Option<Box<dyn Predictor>> {
+    match id {
+        0 => Some(Box::new(RepeatLast)),
+        1 => Some(Box::new(Neutral)),
+        2 => Some(Box::new(ReleaseFire { frames: param.try_into().ok()? })),
+        _ => None,
+    }
+}
+
+// fill in every remote slot of `inputs` from the last confirmed frame
+pub fn predict_remote(predictor: &dyn Predictor, inputs: &mut [Slot], last: &[Slot], local: usize, ahead: u32) {
+    for (i, (slot, last)) in inputs.iter_mut().zip(last).enumerate() {
+        if i != local {
+            *slot = *last;
+            slot.raw = predictor.predict(last, ahead);
+            // a guess at an input that arrives, not another dropped frame
+            slot.broken = false;
+        }
+    }
+}
+
+
+// How often the guesses for connected remote slots matched what the server
+// confirmed afterwards.
+#[derive(Default, Copy, Clone)]
+pub struct PredictionStats {
+    pub predicted: u64,
+    pub correct: u64,
+}
+
+impl PredictionStats {
+
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn record(&mut self, predicted: &[Slot], confirmed: &[Slot], local: usize) {
+        for (i, (p, c)) in predicted.iter().zip(confirmed).enumerate() {
+            if i != local && c.connected {
+                self.predicted += 1;
+                // nobody can guess a checksum, and the game never reads it
+                if Desync::unstamped(p.raw) == Desync::unstamped(c.raw) {
+                    self.correct += 1;
+                }
+            }
+        }
+    }
+
+    // fraction of correct guesses, 1.0 when nothing was predicted yet
+    pub fn accuracy(&self) -> f64 {
+        if self.predicted == 0 {
+            1.0
+        } else {
+            self.correct as f64 / self.predicted as f64
+        }
+    }
+
+    // replay a recorded input stream, guessing each frame from the one `ahead` frames before it
+    pub fn evaluate(predictor: &dyn Predictor, history: &[Vec<Slot>], local: usize, ahead: u32) -> Self {
+        let mut stats = PredictionStats::new();
+        let step: usize = ahead.try_into().unwrap();
+        for i in step..history.len() {
+            let mut predicted = history[i].clone();
+            predict_remote(predictor, &mut predicted, &history[i - step], local, ahead);
+            stats.record(&predicted, &history[i], local);
+        }
+        stats
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::VecDeque;
+
+use crate::game::Game;
+use crate::packet::FramePacket;
+
+
+/***************************
+
+    Relay Room
+
+**************************/
+
+// The part of the relay server that decides what goes into each frame packet,
+// kept apart from any sockets so it can also run in memory.
+//
+// Every tick each connected slot uses up one uploaded input.  A slot with
+// nothing queued is marked dropped for that tick and repeats its last input;
+// the client sees its own dropped bit and uploads that frame again later.
+// What the game does with a dropped slot is up to Game::brokenInput.
+
+pub struct RelayRoom {
+    connected: u64,
+    queued: Vec<VecDeque<i64>>,
+    last: [i64; Game::SlotCount],
+    // ticks broadcast since the room was created
+    pub tick: u32,
+}
+
+impl RelayRoom {
+    // a client can never be further ahead than this many uploads
+    pub const MaxQueued: usize = 64;
+
+    pub fn new() -> Self {
+        Self {
+            connected: 0,
+            queued: vec![VecDeque::new(); Game::SlotCount],
+            last: [0; Game::SlotCount],
+            tick: 0,
+        }
+    }
+
+    // lowest free slot, None when the room is full
+    pub fn join(&mut self) -> Option<u8> {
+        let n = (0..Game::SlotCount).find(|n| self.connected & (1 << n) == 0)?;
+        self.connected |= 1 << n;
+        self.queued[n].clear();
+        self.last[n] = 0;
+        Some(n.try_into().unwrap())
+    }
+
+    pub fn leave(&mut self, slot: u8) {
+        let n = usize::from(slot);
+        self.connected &= !(1 << n);
+        self.queued[n].clear();
+    }
+
+    pub fn connected(&self) -> u64 {
+        self.connected
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.connected == 0
+    }
+
+    // false when the slot is not in the room or is too far ahead
+    pub fn upload(&mut self, slot: u8, raw: i64) -> bool {
+        let n = usize::from(slot);
+        if n >= Game::SlotCount || self.connected & (1 << n) == 0 || self.queued[n].len() >= RelayRoom::MaxQueued {
+            return false;
+        }
+        self.queued[n].push_back(raw & FramePacket::InputMask);
+        true
+    }
+
+    pub fn tick(&mut self) -> FramePacket {
+        self.tick_holding(0)
+    }
+
+    // slots in `held` keep their queued input for a later tick and are dropped
+    // on this one, as if their upload had arrived late
+    pub fn tick_holding(&mut self, held: u64) -> FramePacket {
+        let mut packet = FramePacket::new();
+        packet.connected = self.connected;
+        for n in 0..Game::SlotCount {
+            if self.connected & (1 << n) == 0 {
+                continue;
+            }
+            let next = if held & (1 << n) == 0 { self.queued[n].pop_front() } else { None };
+            match next {
+                Some(raw) => self.last[n] = raw,
+                None => packet.dropped |= 1 << n,
+            }
+            packet.raw[n] = self.last[n];
+        }
+        self.tick += 1;
+        packet
+    }
+}
+
+impl Default for RelayRoom {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,320 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::utility::Slot;
+use crate::game::{Game, Steering, BrokenInput};
+use crate::data::Data;
+use crate::packet::FramePacket;
+use crate::snapshot::{SnapshotError, SnapshotReader};
+
+
+/***************************
+
+    Replay
+
+**************************/
+
+// A .gmreplay file is everything needed to run a match again from Game::init:
+//   "GMRP", u16 version
+//   u32 seed, u64 connected and u64 connectedAtStart at init
+//   u8 steering
+//   u8 broken input policy
+//   u32 frame count, then one frame packet (FramePacket::Size bytes) per
+//   confirmed frame
+//   u32 keyframe count, then per keyframe: u32 frame, u32 length and a game
+//   snapshot of the state after that many frames
+// all little endian.  A simulation change bumps the version, and files from
+// before it are refused rather than played back wrong.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    BadMagic,
+    WrongVersion { found: u16, expected: u16 },
+    Truncated,
+    TrailingBytes(usize),
+    Invalid(&'static str),
+    Snapshot(SnapshotError),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::BadMagic => write!(f, "not a replay"),
+            ReplayError::WrongVersion { found, expected } =>
+                write!(f, "replay format version {} is not supported (expected {})", found, expected),
+            ReplayError::Truncated => write!(f, "replay ends early"),
+            ReplayError::TrailingBytes(n) => write!(f, "replay has {} unexpected trailing bytes", n),
+            ReplayError::Invalid(what) => write!(f, "replay is corrupt: {}", what),
+            ReplayError::Snapshot(e) => write!(f, "replay keyframe: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<SnapshotError> for ReplayError {
+    fn from(e: SnapshotError) -> Self {
+        ReplayError::Snapshot(e)
+    }
+}
+
+
+#[derive(Clone)]
+pub struct Replay {
+    pub seed: u32,
+    pub connected: u64,
+    pub connectedAtStart: u64,
+    pub steering: Steering,
+    pub brokenInput: BrokenInput,
+    pub frames: Vec<FramePacket>,
+    // a snapshot every this many frames while recording, 0 for none
+    pub keyframeInterval: u32,
+    // (frames played, snapshot after them), in order
+    pub keyframes: Vec<(u32, Vec<u8>)>,
+}
+
+impl Replay {
+    pub const MAGIC: [u8; 4] = *b"GMRP";
+    pub const VERSION: u16 = 5;
+    pub const Extension: &'static str = "gmreplay";
+
+    // call with the slots Game::init is about to see
+    pub fn new(seed: u32, slots: &[Slot]) -> Self {
+        let mut connected = 0;
+        let mut connectedAtStart = 0;
+        for (n, slot) in slots.iter().enumerate().take(Game::SlotCount) {
+            if slot.connected {
+                connected |= 1 << n;
+            }
+            if slot.connectedAtStart {
+                connectedAtStart |= 1 << n;
+            }
+        }
+        Self {
+            seed,
+            connected,
+            connectedAtStart,
+            steering: Steering::default(),
+            brokenInput: BrokenInput::default(),
+            frames: Vec::new(),
+            keyframeInterval: 0,
+            keyframes: Vec::new(),
+        }
+    }
+
+    // call after every confirmed update, the slots still hold its inputs
+    pub fn record(&mut self, game: &Game) {
+        self.frames.push(FramePacket::from_slots(&game.slots));
+        let frame: u32 = self.frames.len().try_into().unwrap();
+        if self.keyframeInterval > 0 && frame.is_multiple_of(self.keyframeInterval) {
+            self.keyframes.push((frame, game.save_snapshot()));
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    // a game exactly as it was right after Game::init
+    pub fn start(&self) -> Game {
+        self.start_with(Rc::default())
+    }
+
+    // the same, with the collision handlers the match was played with
+    pub fn start_with(&self, data: Rc<Data>) -> Game {
+        let mut game = Game::with_data(data);
+        for (n, slot) in game.slots.iter_mut().enumerate() {
+            slot.connected = self.connected & (1 << n) != 0;
+            slot.connectedAtStart = self.connectedAtStart & (1 << n) != 0;
+        }
+        game.steering = self.steering;
+        game.brokenInput = self.brokenInput;
+        game.init(self.seed);
+        game
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(32 + self.frames.len() * FramePacket::Size);
+        bytes.extend_from_slice(&Replay::MAGIC);
+        bytes.extend_from_slice(&Replay::VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&self.connected.to_le_bytes());
+        bytes.extend_from_slice(&self.connectedAtStart.to_le_bytes());
+        bytes.push(self.steering.to_u8());
+        bytes.push(self.brokenInput.to_u8());
+        bytes.extend_from_slice(&u32::try_from(self.frames.len()).unwrap().to_le_bytes());
+        for frame in &self.frames {
+            bytes.extend_from_slice(&frame.encode());
+        }
+        bytes.extend_from_slice(&u32::try_from(self.keyframes.len()).unwrap().to_le_bytes());
+        for (frame, state) in &self.keyframes {
+            bytes.extend_from_slice(&frame.to_le_bytes());
+            bytes.extend_from_slice(&u32::try_from(state.len()).unwrap().to_le_bytes());
+            bytes.extend_from_slice(state);
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, ReplayError> {
+        let mut r = ReplayReader { bytes, offset: 0 };
+        if r.read(4).map_err(|_| ReplayError::BadMagic)? != Replay::MAGIC {
+            return Err(ReplayError::BadMagic);
+        }
+        let version = u16::from_le_bytes(r.read_array()?);
+        if version != Replay::VERSION {
+            return Err(ReplayError::WrongVersion { found: version, expected: Replay::VERSION });
+        }
+        let seed = u32::from_le_bytes(r.read_array()?);
+        let connected = u64::from_le_bytes(r.read_array()?);
+        let connectedAtStart = u64::from_le_bytes(r.read_array()?);
+        let steering = Steering::from_u8(u8::from_le_bytes(r.read_array()?)).ok_or(ReplayError::Invalid("unknown steering"))?;
+        let brokenInput = BrokenInput::from_u8(u8::from_le_bytes(r.read_array()?)).ok_or(ReplayError::Invalid("unknown broken input policy"))?;
+
+        let count: usize = u32::from_le_bytes(r.read_array()?).try_into().unwrap();
+        if count > r.remaining() / FramePacket::Size {
+            return Err(ReplayError::Truncated);
+        }
+        let mut frames = Vec::with_capacity(count);
+        for _ in 0..count {
+            // the length is always right, so this cannot fail
+            frames.push(FramePacket::decode(r.read(FramePacket::Size)?).unwrap());
+        }
+
+        let mut keyframes: Vec<(u32, Vec<u8>)> = Vec::new();
+        let count = u32::from_le_bytes(r.read_array()?);
+        for _ in 0..count {
+            let frame = u32::from_le_bytes(r.read_array()?);
+            let len: usize = u32::from_le_bytes(r.read_array()?).try_into().unwrap();
+            let state = r.read(len)?;
+            if usize::try_from(frame).unwrap() > frames.len() || keyframes.last().is_some_and(|k| k.0 >= frame) {
+                return Err(ReplayError::Invalid("keyframes out of order"));
+            }
+            // only the header, the rest is checked when the keyframe is used
+            SnapshotReader::new(state)?;
+            keyframes.push((frame, state.to_vec()));
+        }
+
+        if r.remaining() != 0 {
+            return Err(ReplayError::TrailingBytes(r.remaining()));
+        }
+        // the interval only matters while recording
+        Ok(Self { seed, connected, connectedAtStart, steering, brokenInput, frames, keyframeInterval: 0, keyframes })
+    }
+}
+
+
+struct ReplayReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ReplayReader<'a> {
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.offset
+    }
+
+    fn read(&mut self, n: usize) -> Result<&'a [u8], ReplayError> {
+        if self.remaining() < n {
+            return Err(ReplayError::Truncated);
+        }
+        let ret = &self.bytes[self.offset..self.offset + n];
+        self.offset += n;
+        Ok(ret)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], ReplayError> {
+        Ok(self.read(N)?.try_into().unwrap())
+    }
+}
+
+
+// Feeds a replay back through Game::update, one confirmed frame per step.
+pub struct ReplayPlayer {
+    replay: Replay,
+    game: Game,
+    // frames played so far
+    next: usize,
+}
+
+impl ReplayPlayer {
+
+    pub fn new(replay: Replay) -> Self {
+        ReplayPlayer::with_data(replay, Rc::default())
+    }
+
+    pub fn with_data(replay: Replay, data: Rc<Data>) -> Self {
+        let game = replay.start_with(data);
+        Self { replay, game, next: 0 }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    // the component iterators need mutable access, even just to render
+    pub fn game_mut(&mut self) -> &mut Game {
+        &mut self.game
+    }
+
+    pub fn frame(&self) -> usize {
+        self.next
+    }
+
+    pub fn finished(&self) -> bool {
+        self.next >= self.replay.frames.len()
+    }
+
+    // false once every recorded frame has been played
+    pub fn step(&mut self) -> bool {
+        let Some(frame) = self.replay.frames.get(self.next) else {
+            return false;
+        };
+        frame.apply(&mut self.game.slots);
+        self.game.update();
+        self.next += 1;
+        true
+    }
+
+    // Restore the nearest keyframe at or before `frame` and play forward from
+    // it, or just keep playing when that is closer.  Past the end seeks to the end.
+    pub fn seek(&mut self, frame: usize) -> Result<(), SnapshotError> {
+        let target = frame.min(self.replay.frames.len());
+        let keyframe = self.replay.keyframes.iter()
+            .rev()
+            .find(|k| usize::try_from(k.0).unwrap() <= target);
+        let from = keyframe.map_or(0, |k| usize::try_from(k.0).unwrap());
+
+        if self.next > target || self.next < from {
+            match keyframe {
+                Some((_, state)) => {
+                    self.game.load_snapshot(state)?;
+                    // the stamps already checked before the keyframe are not in it
+                    self.game.desync.reset();
+                }
+                None => self.game = self.replay.start_with(Rc::clone(&self.game.data)),
+            }
+            self.next = from;
+        }
+        while self.next < target {
+            self.step();
+        }
+        Ok(())
+    }
+
+    // false when already at the start
+    pub fn step_back(&mut self) -> Result<bool, SnapshotError> {
+        if self.next == 0 {
+            return Ok(false);
+        }
+        self.seek(self.next - 1)?;
+        Ok(true)
+    }
+}
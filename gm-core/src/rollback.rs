@@ -0,0 +1,243 @@
+use crate::utility::Slot;
+use crate::game::Game;
+use crate::desync::Desync;
+use crate::prediction::{Predictor, RepeatLast, PredictionStats, predict_remote};
+
+
+/***************************
+
+    Rollback
+
+**************************/
+
+// Instead of re-predicting every frame from the last confirmed state, the
+// session keeps the state before each unconfirmed frame together with the
+// inputs it was simulated with.  When a confirmed frame arrives that does not
+// match what was predicted, only that frame and the ones after it are
+// simulated again.
+
+pub struct RollbackFrame {
+    // state before the frame was simulated
+    state: Game,
+    // every slot's input the frame was simulated with, stamped with its frame
+    inputs: Vec<Slot>,
+    // our own input for the frame
+    local: i64,
+}
+
+impl RollbackFrame {
+    fn new() -> Self {
+        Self {
+            state: Game::new(),
+            inputs: vec![Default::default(); Game::SlotCount],
+            local: 0,
+        }
+    }
+}
+
+pub struct RollbackSession {
+    local: usize,
+    // newest predicted state, this is what gets rendered
+    game: Game,
+    ring: Vec<RollbackFrame>,
+    // frames whose inputs came back from the server
+    confirmed: u32,
+    // frames simulated so far, confirmed or predicted
+    head: u32,
+    lastConfirmed: Vec<Slot>,
+    rollbacks: u32,
+    resimulated: u32,
+    predictor: Box<dyn Predictor>,
+    stats: PredictionStats,
+}
+
+impl RollbackSession {
+    pub const DefaultCapacity: usize = 16;
+
+    pub fn new(local: usize, capacity: usize) -> Self {
+        Self {
+            local,
+            game: Game::new(),
+            ring: (0..capacity.max(1)).map(|_| RollbackFrame::new()).collect(),
+            confirmed: 0,
+            head: 0,
+            lastConfirmed: vec![Default::default(); Game::SlotCount],
+            rollbacks: 0,
+            resimulated: 0,
+            predictor: Box::new(RepeatLast),
+            stats: PredictionStats::new(),
+        }
+    }
+
+    pub fn init(&mut self, seed: u32, slots: &[Slot]) {
+        self.game.slots.copy_from_slice(slots);
+        self.game.init(seed);
+        self.lastConfirmed.copy_from_slice(slots);
+        self.confirmed = 0;
+        self.head = 0;
+        self.rollbacks = 0;
+        self.resimulated = 0;
+        self.stats = PredictionStats::new();
+    }
+
+    pub fn set_predictor(&mut self, predictor: Box<dyn Predictor>) {
+        self.predictor = predictor;
+    }
+
+    pub fn stats(&self) -> PredictionStats {
+        self.stats
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    // the component iterators need mutable access, even just to render
+    pub fn game_mut(&mut self) -> &mut Game {
+        &mut self.game
+    }
+
+    // state after the last confirmed frame
+    pub fn confirmed_game(&self) -> &Game {
+        if self.confirmed < self.head {
+            &self.ring[self.index(self.confirmed)].state
+        } else {
+            &self.game
+        }
+    }
+
+    // same numbering as Game::frame
+    pub fn confirmed_frame(&self) -> u32 {
+        self.confirmed
+    }
+
+    pub fn head_frame(&self) -> u32 {
+        self.head
+    }
+
+    pub fn rollbacks(&self) -> u32 {
+        self.rollbacks
+    }
+
+    pub fn resimulated(&self) -> u32 {
+        self.resimulated
+    }
+
+    fn index(&self, frame: u32) -> usize {
+        usize::try_from(frame).unwrap() % self.ring.len()
+    }
+
+    // false once the ring is full of unconfirmed frames, the caller should wait
+    pub fn can_predict(&self) -> bool {
+        usize::try_from(self.head - self.confirmed).unwrap() < self.ring.len()
+    }
+
+    // simulate one frame ahead with our own input and a guess for everyone else
+    pub fn predict(&mut self, local: i64) -> bool {
+        if !self.can_predict() {
+            return false;
+        }
+        let frame = self.head;
+        let inputs = self.predicted_inputs(local, self.head - self.confirmed + 1);
+        self.simulate(frame, &inputs, local);
+        self.head += 1;
+        true
+    }
+
+    // the next confirmed frame's inputs, as received from the server
+    pub fn confirm(&mut self, slots: &[Slot]) {
+        let frame = self.confirmed;
+        self.lastConfirmed.copy_from_slice(slots);
+
+        if frame == self.head {
+            // nothing was predicted, just run it
+            let local = slots[self.local].raw;
+            self.simulate(frame, slots, local);
+            self.head += 1;
+        } else {
+            let i = self.index(frame);
+            self.stats.record(&self.ring[i].inputs, slots, self.local);
+            let matches = self.ring[i].inputs.iter().zip(slots).all(|(a, b)| RollbackSession::same_input(a, b));
+            if !matches {
+                self.rollback(frame, slots);
+            } else {
+                self.restamp(frame, slots);
+            }
+        }
+
+        self.confirmed += 1;
+
+        self.game.desync.check(slots);
+        let confirmed = self.confirmed_game();
+        if Desync::is_checkpoint(confirmed.frame) {
+            let (frame, sum) = (confirmed.frame, confirmed.checksum());
+            self.game.desync.record(frame, sum);
+        }
+    }
+
+    fn rollback(&mut self, frame: u32, slots: &[Slot]) {
+        self.rollbacks += 1;
+
+        let i = self.index(frame);
+        self.game.smartCopy(&self.ring[i].state);
+
+        let local = self.ring[i].local;
+        self.simulate(frame, slots, local);
+
+        for f in (frame + 1)..self.head {
+            let local = self.ring[self.index(f)].local;
+            let inputs = self.predicted_inputs(local, f - frame);
+            self.simulate(f, &inputs, local);
+            self.resimulated += 1;
+        }
+    }
+
+    // The stamps the guesses got wrong changed nothing that was simulated, but
+    // the confirmed state keeps its frame's inputs, so it still needs the real ones.
+    fn restamp(&mut self, frame: u32, slots: &[Slot]) {
+        let i = self.index(frame);
+        for (input, slot) in self.ring[i].inputs.iter_mut().zip(slots) {
+            input.raw = slot.raw;
+        }
+        let after = if frame + 1 < self.head {
+            let next = self.index(frame + 1);
+            &mut self.ring[next].state
+        } else {
+            &mut self.game
+        };
+        for (input, slot) in after.slots.iter_mut().zip(slots) {
+            input.raw = slot.raw;
+        }
+    }
+
+    fn simulate(&mut self, frame: u32, inputs: &[Slot], local: i64) {
+        let i = self.index(frame);
+        let entry = &mut self.ring[i];
+        entry.state.smartCopy(&self.game);
+        entry.local = local;
+
+        for (slot, input) in self.game.slots.iter_mut().zip(inputs) {
+            slot.receive(input);
+        }
+        self.game.simulate();
+        self.ring[i].inputs.copy_from_slice(&self.game.slots);
+    }
+
+    // `ahead` frames past the last confirmed one
+    fn predicted_inputs(&self, local: i64, ahead: u32) -> Vec<Slot> {
+        let mut inputs = self.lastConfirmed.clone();
+        predict_remote(self.predictor.as_ref(), &mut inputs, &self.lastConfirmed, self.local, ahead);
+        inputs[self.local].raw = local;
+        inputs[self.local].broken = false;
+        inputs
+    }
+
+    // desync stamps change every checkpoint and are never simulated, so they
+    // don't count as a misprediction
+    fn same_input(a: &Slot, b: &Slot) -> bool {
+        a.connected == b.connected &&
+        a.connectedAtStart == b.connectedAtStart &&
+        a.broken == b.broken &&
+        Desync::unstamped(a.raw) == Desync::unstamped(b.raw)
+    }
+}
@@ -0,0 +1,448 @@
+use std::fmt;
+
+use crate::containers::IndexTable;
+use crate::twister::MersenneTwister;
+use crate::vector::Vector2;
+use crate::fixed::{Fixed, FixedVector2};
+use crate::components::*;
+use crate::utility::{Control, Slot, GlobalState};
+use crate::pools::{CpPack, Cp};
+
+
+/***************************
+
+    Snapshot
+
+**************************/
+
+// Layout: "GMSS", u16 version, then every field smartCopy copies, all little
+// endian, vectors prefixed with a u32 length.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotError {
+    BadMagic,
+    WrongVersion { found: u16, expected: u16 },
+    Truncated,
+    TrailingBytes(usize),
+    Invalid(&'static str),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::BadMagic => write!(f, "not a game snapshot"),
+            SnapshotError::WrongVersion { found, expected } =>
+                write!(f, "snapshot format version {} is not supported (expected {})", found, expected),
+            SnapshotError::Truncated => write!(f, "snapshot ends early"),
+            SnapshotError::TrailingBytes(n) => write!(f, "snapshot has {} unexpected trailing bytes", n),
+            SnapshotError::Invalid(what) => write!(f, "snapshot is corrupt: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+
+pub struct SnapshotWriter {
+    bytes: Vec<u8>,
+}
+
+impl SnapshotWriter {
+    pub const MAGIC: [u8; 4] = *b"GMSS";
+    pub const VERSION: u16 = 6;
+
+    pub fn new() -> Self {
+        let mut w = Self { bytes: Vec::new() };
+        w.write_bytes(&SnapshotWriter::MAGIC);
+        w.write_u16(SnapshotWriter::VERSION);
+        w
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) { self.bytes.extend_from_slice(bytes); }
+    pub fn write_bool(&mut self, v: bool) { self.bytes.push(u8::from(v)); }
+    pub fn write_u8(&mut self, v: u8) { self.bytes.push(v); }
+    pub fn write_i8(&mut self, v: i8) { self.write_bytes(&v.to_le_bytes()); }
+    pub fn write_u16(&mut self, v: u16) { self.write_bytes(&v.to_le_bytes()); }
+    pub fn write_i16(&mut self, v: i16) { self.write_bytes(&v.to_le_bytes()); }
+    pub fn write_u32(&mut self, v: u32) { self.write_bytes(&v.to_le_bytes()); }
+    pub fn write_i32(&mut self, v: i32) { self.write_bytes(&v.to_le_bytes()); }
+    pub fn write_i64(&mut self, v: i64) { self.write_bytes(&v.to_le_bytes()); }
+
+    pub fn write_len(&mut self, len: usize) {
+        self.write_u32(len.try_into().unwrap());
+    }
+}
+
+impl Default for SnapshotWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+pub struct SnapshotReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> SnapshotReader<'a> {
+
+    // checks the header, so a reader is always positioned on the first field
+    pub fn new(bytes: &'a [u8]) -> Result<Self, SnapshotError> {
+        let mut r = Self { bytes, offset: 0 };
+        if r.read_bytes(4).map_err(|_| SnapshotError::BadMagic)? != SnapshotWriter::MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        let version = r.read_u16()?;
+        if version != SnapshotWriter::VERSION {
+            return Err(SnapshotError::WrongVersion { found: version, expected: SnapshotWriter::VERSION });
+        }
+        Ok(r)
+    }
+
+    pub fn finish(self) -> Result<(), SnapshotError> {
+        match self.bytes.len() - self.offset {
+            0 => Ok(()),
+            n => Err(SnapshotError::TrailingBytes(n)),
+        }
+    }
+
+    pub fn read_bytes(&mut self, n: usize) -> Result<&'a [u8], SnapshotError> {
+        if self.bytes.len() - self.offset < n {
+            return Err(SnapshotError::Truncated);
+        }
+        let ret = &self.bytes[self.offset..self.offset + n];
+        self.offset += n;
+        Ok(ret)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], SnapshotError> {
+        Ok(self.read_bytes(N)?.try_into().unwrap())
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, SnapshotError> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(SnapshotError::Invalid("bool out of range")),
+        }
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, SnapshotError> { Ok(u8::from_le_bytes(self.read_array()?)) }
+    pub fn read_i8(&mut self) -> Result<i8, SnapshotError> { Ok(i8::from_le_bytes(self.read_array()?)) }
+    pub fn read_u16(&mut self) -> Result<u16, SnapshotError> { Ok(u16::from_le_bytes(self.read_array()?)) }
+    pub fn read_i16(&mut self) -> Result<i16, SnapshotError> { Ok(i16::from_le_bytes(self.read_array()?)) }
+    pub fn read_u32(&mut self) -> Result<u32, SnapshotError> { Ok(u32::from_le_bytes(self.read_array()?)) }
+    pub fn read_i32(&mut self) -> Result<i32, SnapshotError> { Ok(i32::from_le_bytes(self.read_array()?)) }
+    pub fn read_i64(&mut self) -> Result<i64, SnapshotError> { Ok(i64::from_le_bytes(self.read_array()?)) }
+
+    // never trust a length further than the bytes that are actually left
+    pub fn read_len(&mut self) -> Result<usize, SnapshotError> {
+        let len: usize = self.read_u32()?.try_into().unwrap();
+        if len > self.bytes.len() - self.offset {
+            return Err(SnapshotError::Truncated);
+        }
+        Ok(len)
+    }
+}
+
+
+// Anything that is part of the copied game state
+pub trait Snapshot: Sized {
+    fn save(&self, w: &mut SnapshotWriter);
+    fn load(r: &mut SnapshotReader) -> Result<Self, SnapshotError>;
+}
+
+impl<T: Snapshot> Snapshot for Vec<T> {
+    fn save(&self, w: &mut SnapshotWriter) {
+        w.write_len(self.len());
+        for v in self {
+            v.save(w);
+        }
+    }
+
+    fn load(r: &mut SnapshotReader) -> Result<Self, SnapshotError> {
+        let len = r.read_len()?;
+        let mut ret = Vec::with_capacity(len);
+        for _ in 0..len {
+            ret.push(T::load(r)?);
+        }
+        Ok(ret)
+    }
+}
+
+impl Snapshot for u16 {
+    fn save(&self, w: &mut SnapshotWriter) {
+        w.write_u16(*self);
+    }
+
+    fn load(r: &mut SnapshotReader) -> Result<Self, SnapshotError> {
+        r.read_u16()
+    }
+}
+
+impl Snapshot for IndexTable {
+    fn save(&self, w: &mut SnapshotWriter) {
+        w.write_u16(self.head);
+        self.list.save(w);
+    }
+
+    fn load(r: &mut SnapshotReader) -> Result<Self, SnapshotError> {
+        let head = r.read_u16()?;
+        let list: Vec<u16> = Vec::load(r)?;
+        // allocate hands out the length as the next index
+        if list.len() > usize::from(u16::MAX) {
+            return Err(SnapshotError::Invalid("index table too long"));
+        }
+        if list.is_empty() || usize::from(head) >= list.len() || list.iter().any(|v| usize::from(*v) >= list.len()) {
+            return Err(SnapshotError::Invalid("index table out of range"));
+        }
+        Ok(Self { head, list })
+    }
+}
+
+impl Snapshot for MersenneTwister {
+    fn save(&self, w: &mut SnapshotWriter) {
+        w.write_i32(self.p);
+        w.write_i32(self.q);
+        w.write_i32(self.r);
+        for x in &self.x {
+            w.write_u32(*x);
+        }
+    }
+
+    fn load(r: &mut SnapshotReader) -> Result<Self, SnapshotError> {
+        let mut ret = MersenneTwister::new();
+        ret.p = r.read_i32()?;
+        ret.q = r.read_i32()?;
+        ret.r = r.read_i32()?;
+        for x in ret.x.iter_mut() {
+            *x = r.read_u32()?;
+        }
+        let range = 0..i32::try_from(MersenneTwister::N).unwrap();
+        if !range.contains(&ret.p) || !range.contains(&ret.q) || !range.contains(&ret.r) {
+            return Err(SnapshotError::Invalid("random state out of range"));
+        }
+        Ok(ret)
+    }
+}
+
+impl Snapshot for Vector2 {
+    fn save(&self, w: &mut SnapshotWriter) {
+        w.write_i32(self.x);
+        w.write_i32(self.y);
+    }
+
+    fn load(r: &mut SnapshotReader) -> Result<Self, SnapshotError> {
+        Ok(Vector2::from(r.read_i32()?, r.read_i32()?))
+    }
+}
+
+impl Snapshot for Fixed {
+    fn save(&self, w: &mut SnapshotWriter) {
+        w.write_i32(self.to_bits());
+    }
+
+    fn load(r: &mut SnapshotReader) -> Result<Self, SnapshotError> {
+        Ok(Fixed::from_bits(r.read_i32()?))
+    }
+}
+
+impl Snapshot for FixedVector2 {
+    fn save(&self, w: &mut SnapshotWriter) {
+        self.x.save(w);
+        self.y.save(w);
+    }
+
+    fn load(r: &mut SnapshotReader) -> Result<Self, SnapshotError> {
+        Ok(FixedVector2::from(Fixed::load(r)?, Fixed::load(r)?))
+    }
+}
+
+impl Snapshot for Animator {
+    fn save(&self, w: &mut SnapshotWriter) {
+        w.write_u16(self.frame);
+        w.write_u16(self.count);
+    }
+
+    fn load(r: &mut SnapshotReader) -> Result<Self, SnapshotError> {
+        Ok(Self { frame: r.read_u16()?, count: r.read_u16()? })
+    }
+}
+
+impl Snapshot for Body {
+    fn save(&self, w: &mut SnapshotWriter) {
+        self.position.save(w);
+        self.velocity.save(w);
+        self.size.save(w);
+    }
+
+    fn load(r: &mut SnapshotReader) -> Result<Self, SnapshotError> {
+        Ok(Self { position: FixedVector2::load(r)?, velocity: FixedVector2::load(r)?, size: Vector2::load(r)? })
+    }
+}
+
+impl Snapshot for Player {
+    fn save(&self, w: &mut SnapshotWriter) {
+        w.write_i8(self.slot);
+        w.write_u16(self.delayFire);
+        w.write_u16(self.damage);
+    }
+
+    fn load(r: &mut SnapshotReader) -> Result<Self, SnapshotError> {
+        Ok(Self { slot: r.read_i8()?, delayFire: r.read_u16()?, damage: r.read_u16()? })
+    }
+}
+
+impl Snapshot for Enemy {
+    fn save(&self, w: &mut SnapshotWriter) {
+        w.write_i8(self.direction);
+        w.write_u8(self.counter);
+        w.write_u16(self.delayFire);
+    }
+
+    fn load(r: &mut SnapshotReader) -> Result<Self, SnapshotError> {
+        Ok(Self { direction: r.read_i8()?, counter: r.read_u8()?, delayFire: r.read_u16()? })
+    }
+}
+
+impl Snapshot for ObjType {
+    fn save(&self, w: &mut SnapshotWriter) {
+        w.write_u8(self.0);
+    }
+
+    fn load(r: &mut SnapshotReader) -> Result<Self, SnapshotError> {
+        let value = r.read_u8()?;
+        if value >= ObjType::Count {
+            return Err(SnapshotError::Invalid("unknown object type"));
+        }
+        Ok(ObjType::from(value))
+    }
+}
+
+impl Snapshot for Cf {
+    fn save(&self, w: &mut SnapshotWriter) {
+        w.write_u8(self.0);
+    }
+
+    fn load(r: &mut SnapshotReader) -> Result<Self, SnapshotError> {
+        Ok(Cf::from(r.read_u8()?))
+    }
+}
+
+impl Snapshot for Control {
+    fn save(&self, w: &mut SnapshotWriter) {
+        w.write_u8(self.state);
+        w.write_i16(self.x);
+        w.write_bool(self.nonEmpty);
+        w.write_bool(self.left);
+        w.write_bool(self.right);
+        w.write_bool(self.primary);
+        w.write_bool(self.steer);
+        w.write_u32(self.debug);
+    }
+
+    fn load(r: &mut SnapshotReader) -> Result<Self, SnapshotError> {
+        Ok(Self {
+            state: r.read_u8()?,
+            x: r.read_i16()?,
+            nonEmpty: r.read_bool()?,
+            left: r.read_bool()?,
+            right: r.read_bool()?,
+            primary: r.read_bool()?,
+            steer: r.read_bool()?,
+            debug: r.read_u32()?,
+        })
+    }
+}
+
+impl Snapshot for Slot {
+    fn save(&self, w: &mut SnapshotWriter) {
+        w.write_u32(self.frame);
+        w.write_bool(self.connected);
+        w.write_bool(self.connectedAtStart);
+        w.write_bool(self.broken);
+        w.write_i64(self.raw);
+        self.input.save(w);
+    }
+
+    fn load(r: &mut SnapshotReader) -> Result<Self, SnapshotError> {
+        Ok(Self {
+            frame: r.read_u32()?,
+            connected: r.read_bool()?,
+            connectedAtStart: r.read_bool()?,
+            broken: r.read_bool()?,
+            raw: r.read_i64()?,
+            input: Control::load(r)?,
+        })
+    }
+}
+
+impl Snapshot for GlobalState {
+    fn save(&self, w: &mut SnapshotWriter) {
+        w.write_bool(self.playing);
+        self.enemySpeed.save(w);
+        w.write_i32(self.enemyCount);
+        w.write_u16(self.textType);
+        w.write_i32(self.textAnimate);
+    }
+
+    fn load(r: &mut SnapshotReader) -> Result<Self, SnapshotError> {
+        Ok(Self {
+            playing: r.read_bool()?,
+            enemySpeed: Fixed::load(r)?,
+            enemyCount: r.read_i32()?,
+            textType: r.read_u16()?,
+            textAnimate: r.read_i32()?,
+        })
+    }
+}
+
+impl Snapshot for CpPack {
+    fn save(&self, w: &mut SnapshotWriter) {
+        self.generation.save(w);
+        self.comp.save(w);
+        self.objectId.save(w);
+        self.body.save(w);
+        self.player.save(w);
+        self.enemy.save(w);
+        self.animator.save(w);
+    }
+
+    fn load(r: &mut SnapshotReader) -> Result<Self, SnapshotError> {
+        let pack = Self {
+            generation: Vec::load(r)?,
+            comp: Vec::load(r)?,
+            objectId: Vec::load(r)?,
+            body: Vec::load(r)?,
+            player: Vec::load(r)?,
+            enemy: Vec::load(r)?,
+            animator: Vec::load(r)?,
+        };
+        let s = pack.size();
+        if pack.comp.len() != s || pack.objectId.len() != s || pack.body.len() != s ||
+            pack.player.len() != s || pack.enemy.len() != s || pack.animator.len() != s {
+            return Err(SnapshotError::Invalid("component columns differ in length"));
+        }
+        Ok(pack)
+    }
+}
+
+impl Snapshot for Cp {
+    fn save(&self, w: &mut SnapshotWriter) {
+        self.manager.save(w);
+        self.pack.save(w);
+    }
+
+    fn load(r: &mut SnapshotReader) -> Result<Self, SnapshotError> {
+        let cp = Self { manager: IndexTable::load(r)?, pack: CpPack::load(r)? };
+        if cp.pack.size() > cp.manager.list.len() {
+            return Err(SnapshotError::Invalid("more components than entities"));
+        }
+        Ok(cp)
+    }
+}
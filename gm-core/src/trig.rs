@@ -0,0 +1,184 @@
+use std::ops;
+
+use crate::fixed::{Fixed, FixedVector2};
+
+
+/***************************
+
+    Angle
+
+**************************/
+
+// A binary angle, 65536 to a full turn, so adding angles wraps around for
+// free.  0 points along +x and angles grow toward +y, which is up the screen.
+//
+// sin and cos come from a quarter wave table built at compile time with
+// integer maths only, and atan2 searches that same table, so every platform
+// gets the same bits.
+
+#[derive(Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Angle(u16);
+
+impl Angle {
+    pub const Right: Angle = Angle(0);
+    pub const Up: Angle = Angle(0x4000);
+    pub const Left: Angle = Angle(0x8000);
+    pub const Down: Angle = Angle(0xc000);
+
+    // table entries per quarter turn, and the angle between two of them
+    const Steps: usize = 256;
+    const StepBits: u32 = 6;
+    const Quarter: i32 = 0x4000;
+
+    pub const fn from_bits(bits: u16) -> Self {
+        Self(bits)
+    }
+
+    pub const fn to_bits(self) -> u16 {
+        self.0
+    }
+
+    // rounded to the nearest binary angle
+    pub fn from_degrees(degrees: i32) -> Self {
+        let d = i64::from(degrees.rem_euclid(360));
+        Angle::wrap((d * 65536 + 180) / 360)
+    }
+
+    fn wrap(v: i64) -> Self {
+        Self(u16::try_from(v.rem_euclid(65536)).unwrap())
+    }
+
+    pub fn sin(self) -> Fixed {
+        let a = i32::from(self.0);
+        let within = a & (Angle::Quarter - 1);
+        let v = match a / Angle::Quarter {
+            0 => Angle::quarter_sin(within),
+            1 => Angle::quarter_sin(Angle::Quarter - within),
+            2 => -Angle::quarter_sin(within),
+            _ => -Angle::quarter_sin(Angle::Quarter - within),
+        };
+        Fixed::from_bits(v)
+    }
+
+    pub fn cos(self) -> Fixed {
+        (self + Angle::Up).sin()
+    }
+
+    // 0..=Quarter, linear between table entries
+    fn quarter_sin(a: i32) -> i32 {
+        let i = usize::try_from(a >> Angle::StepBits).unwrap();
+        let frac = a & ((1 << Angle::StepBits) - 1);
+        if frac == 0 {
+            return SinTable[i];
+        }
+        SinTable[i] + (((SinTable[i + 1] - SinTable[i]) * frac) >> Angle::StepBits)
+    }
+
+    // the angle of (x, y), nearest binary angle; Right for (0, 0)
+    pub fn atan2(y: Fixed, x: Fixed) -> Self {
+        let ax = i64::from(x.to_bits()).abs();
+        let ay = i64::from(y.to_bits()).abs();
+        if ax == 0 && ay == 0 {
+            return Angle::Right;
+        }
+
+        // ax * sin(a) - ay * cos(a) only grows across the first quadrant, find
+        // where it crosses zero
+        let error = |a: i32| {
+            let angle = Angle::wrap(i64::from(a));
+            ax * i64::from(angle.sin().to_bits()) - ay * i64::from(angle.cos().to_bits())
+        };
+        let mut low = 0;
+        let mut high = Angle::Quarter;
+        while low < high {
+            let mid = (low + high) / 2;
+            if error(mid) < 0 { low = mid + 1; } else { high = mid; }
+        }
+        if low > 0 && error(low - 1).abs() <= error(low).abs() {
+            low -= 1;
+        }
+
+        let a = i64::from(low);
+        let full = match (x.to_bits() < 0, y.to_bits() < 0) {
+            (false, false) => a,
+            (true, false) => 0x8000 - a,
+            (true, true) => 0x8000 + a,
+            (false, true) => -a,
+        };
+        Angle::wrap(full)
+    }
+}
+
+impl ops::Add for Angle {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self(self.0.wrapping_add(other.0))
+    }
+}
+
+impl ops::Sub for Angle {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self(self.0.wrapping_sub(other.0))
+    }
+}
+
+impl ops::Neg for Angle {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(self.0.wrapping_neg())
+    }
+}
+
+impl ops::Mul<i32> for Angle {
+    type Output = Self;
+
+    fn mul(self, other: i32) -> Self {
+        Angle::wrap(i64::from(self.0) * i64::from(other))
+    }
+}
+
+
+// sin(k / Steps of a quarter turn) in 16.16, from a Taylor series worked in
+// 2.30 integers so the table never depends on the platform's floats
+const SinTable: [i32; Angle::Steps + 1] = sin_table();
+
+const fn sin_table() -> [i32; Angle::Steps + 1] {
+    const One: i64 = 1 << 30;
+    const Pi: i64 = 3_373_259_426;
+    let mut table = [0; Angle::Steps + 1];
+    let mut k = 0;
+    while k <= Angle::Steps {
+        let x = (k as i64) * Pi / (2 * Angle::Steps as i64);
+        let x2 = x * x / One;
+        let mut term = x;
+        let mut sum = x;
+        let mut n = 1;
+        while n < 8 {
+            term = -(term * x2 / One) / ((2 * n) * (2 * n + 1));
+            sum += term;
+            n += 1;
+        }
+        // 2.30 to 16.16, rounded
+        table[k] = ((sum + (1 << 13)) >> 14) as i32;
+        k += 1;
+    }
+    table
+}
+
+const _: () = assert!(SinTable[0] == 0 && SinTable[Angle::Steps] == 1 << Fixed::FracBits, "sine table is off");
+
+
+impl FixedVector2 {
+    // `length` long, pointing at `angle`
+    pub fn from_angle(angle: Angle, length: Fixed) -> Self {
+        FixedVector2::from(angle.cos() * length, angle.sin() * length)
+    }
+
+    pub fn angle(self) -> Angle {
+        Angle::atan2(self.y, self.x)
+    }
+}
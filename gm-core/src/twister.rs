@@ -0,0 +1,116 @@
+use std::convert::TryFrom;
+
+
+/***************************
+
+    MersenneTwister
+
+**************************/
+
+#[derive(Copy, Clone)]
+pub struct MersenneTwister {
+    pub p: i32,
+    pub q: i32,
+    pub r: i32,
+    pub x: [u32; MersenneTwister::N],
+}
+
+impl MersenneTwister {
+     // static data and functions
+    pub const N: usize = 624;
+    pub const M: i32 = 397;
+    pub const UPPER_MASK: u32 = 0x80000000;
+    pub const LOWER_MASK: u32 = 0x7fffffff;
+    pub const MATRIX_A: u32 = 0x9908b0df;
+
+    pub fn new() -> Self {
+        Self {
+            p: 0,
+            q: 0,
+            r: 0,
+            x: [0; MersenneTwister::N],
+        }
+    }
+    
+    pub fn from_zero(_s: u32) -> Self {
+        let mut r = MersenneTwister::new();
+        r.set_seed(0);
+        r
+    }
+
+    pub fn from(s: u32) -> Self {
+        let mut r = MersenneTwister::new();
+        r.set_seed(s);
+        r
+    }
+
+    pub fn imul(a: u32, b: u32) -> u32 {
+        let al: u32 = a & 0xffff;
+        let ah: u32 = a >> 16;
+        let bl: u32 = b & 0xffff;
+        let bh: u32 = b >> 16;
+        let ml: u32 = al * bl;
+        let mh: u32 = ( (((ml >> 16) + al * bh) & 0xffff) + ah * bl ) & 0xffff;
+        (mh << 16) | (ml & 0xffff)
+    }
+
+    pub fn set_seed(&mut self, s: u32) {
+        self.x[0] = s;
+        for i in 1..MersenneTwister::N {
+            let i_u32: u32 = i.try_into().unwrap();
+            self.x[i] = MersenneTwister::imul( 1812433253, self.x[i - 1] ^ (self.x[i - 1] >> 30) ) + i_u32;
+            self.x[i] &= 0xffffffff;
+        }
+        self.p = 0;
+        self.q = 1;
+        self.r = MersenneTwister::M;
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let p: usize = self.p.try_into().unwrap();
+        let q: usize = self.q.try_into().unwrap();
+        let r: usize = self.r.try_into().unwrap();
+
+        let mut y: u32 = (self.x[p] & MersenneTwister::UPPER_MASK) | (self.x[q] & MersenneTwister::LOWER_MASK);
+        self.x[p] = self.x[r] ^ (y >> 1) ^ ((y & 1) * MersenneTwister::MATRIX_A);
+        y = self.x[p];
+
+        self.p += 1;
+        self.q += 1;
+        self.r += 1;
+
+        let N: i32 = MersenneTwister::N.try_into().unwrap();
+        if self.p == N { self.p = 0; }
+        if self.q == N { self.q = 0; }
+        if self.r == N { self.r = 0; }
+
+        y ^= y >> 11;
+        y ^= (y << 7) & 0x9d2c5680;
+        y ^= (y << 15) & 0xefc60000;
+        y ^= y >> 18;
+
+        y
+    }
+
+    pub fn next_u32_bits(&mut self, bits: u32) -> u32 {
+        self.next_u32() >> (32 - bits)
+    }
+
+    pub fn next_from_zero(&mut self, max: i32) -> i32 {        
+        let n = self.next_u32() % u32::try_from(max + 1).unwrap();
+        i32::try_from(n).unwrap()
+    }
+
+    pub fn next_range(&mut self, min: i32, max: i32) -> i32 {        
+        let n = self.next_u32() % u32::try_from(max + 1 - min).unwrap();
+        i32::try_from(n).unwrap() + min
+    }
+
+}
+
+impl Default for MersenneTwister {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
@@ -0,0 +1,399 @@
+use crate::entity::Entity;
+use crate::vector::Vector2;
+use crate::fixed::{Fixed, FixedVector2};
+use crate::trig::Angle;
+use crate::components::ObjType;
+
+
+/***************************
+
+    Utility Objects
+
+**************************/
+// bits start..end of an i64, end can be 64
+#[derive(Default, Copy, Clone)]
+pub struct BitMask {
+    pub mask:i64,
+    pub shift:usize,
+    pub width:usize,
+    // decode sign extends from the top bit of the field
+    pub signed:bool,
+}
+
+impl BitMask {
+    pub fn new() -> Self {
+        Self {
+            mask: 0,
+            shift: 0,
+            width: 0,
+            signed: false,
+        }
+    }
+    
+    pub const fn const_from(start:usize, end:usize) -> Self {
+        let mut ender = end;
+        if ender > 64 { ender = 64; }
+
+        let mut mask = 0x0000000000000000;
+        let mut i = start;
+
+        while i < ender {
+            mask |= 1 << i;
+            i += 1;
+        }
+
+        Self {
+            mask: mask,
+            shift: start,
+            width: ender.saturating_sub(start),
+            signed: false,
+        }
+    }
+
+    pub const fn const_signed(start:usize, end:usize) -> Self {
+        let mut ret = BitMask::const_from(start, end);
+        ret.signed = true;
+        ret
+    }
+
+    pub fn from(start:usize, end:usize) -> Self {
+        BitMask::const_from(start, end)
+    }
+
+    pub fn signed(start:usize, end:usize) -> Self {
+        BitMask::const_signed(start, end)
+    }
+
+    pub fn decode(&self, value:i64) -> i64 {
+        if self.width == 0 {
+            return 0;
+        }
+        if self.signed == true {
+            // move the field to the top, then shift back down arithmetically
+            (value << (64 - self.shift - self.width)) >> (64 - self.width)
+        } else {
+            // clear what an arithmetic shift drags in from bit 63
+            ((value & self.mask) >> self.shift) & BitMask::low(self.width)
+        }
+    }
+
+    pub fn encode(&self, value:i64) -> i64 {
+        (value << self.shift) & self.mask
+    }
+
+    // the lowest `width` bits set
+    const fn low(width:usize) -> i64 {
+        if width >= 64 { -1 } else { (1 << width) - 1 }
+    }
+
+    // Spread a payload too big for one input over the field of as many
+    // consecutive inputs as it takes, a few bytes at a time.
+    pub fn split(&self, payload:&[u8]) -> Vec<i64> {
+        let chunk = self.width / 8;
+        assert!(chunk > 0, "field is narrower than a byte");
+        payload.chunks(chunk).map(|bytes| {
+            let mut buffer = [0u8; 8];
+            buffer[..bytes.len()].copy_from_slice(bytes);
+            self.encode(i64::from_le_bytes(buffer))
+        }).collect()
+    }
+
+    // the first `len` bytes of a payload split over these inputs
+    pub fn join(&self, raws:&[i64], len:usize) -> Vec<u8> {
+        let chunk = self.width / 8;
+        assert!(chunk > 0, "field is narrower than a byte");
+        let mut payload = Vec::with_capacity(raws.len() * chunk);
+        for raw in raws {
+            // signed or not, the low bytes are the same
+            payload.extend_from_slice(&self.decode(*raw).to_le_bytes()[..chunk]);
+        }
+        payload.truncate(len);
+        payload
+    }
+}
+
+
+#[derive(Default, Copy, Clone)]
+pub struct Control {
+    // 3 bits state - 0 is unready, 1+ difficulty, 7 synced
+    // non empty 1 bit
+    // left, right, primary 3 bits
+    pub state: u8,
+    pub x: i16,
+
+    // 10 bits, 6 left over
+    pub nonEmpty: bool,
+    pub left: bool,
+    pub right: bool,
+
+    pub primary: bool,
+    pub steer: bool,
+
+    pub debug: u32,
+}
+    
+// maybe combine this with above
+#[derive(Default, Copy, Clone)]
+pub struct Slot {
+    pub frame: u32, // the frame this input was simulated in
+    pub connected: bool,
+    pub connectedAtStart: bool,
+    pub broken: bool,
+    pub raw: i64,
+    pub input: Control,
+}
+
+impl Slot {
+    // everything but `input`, which a dropped frame may carry over from the
+    // frame before, see BrokenInput
+    pub fn receive(&mut self, from: &Slot) {
+        self.connected = from.connected;
+        self.connectedAtStart = from.connectedAtStart;
+        self.broken = from.broken;
+        self.raw = from.raw;
+    }
+}
+
+#[derive(Default, Copy, Clone)]
+pub struct GlobalState {
+    pub playing: bool,
+    pub enemySpeed: Fixed,
+    pub enemyCount: i32,
+    pub textType: u16,
+    pub textAnimate: i32,
+}
+
+#[derive(Default, Copy, Clone)]
+pub struct Bounds {
+    pub btype: u8,
+    pub entity: Entity,
+    // around everything the body touched this frame, start to end
+    pub lower: Vector2,
+    pub upper: Vector2,
+    // how far it moved this frame
+    pub d: Vector2,
+}
+/*
+impl PartialOrd for Bounds {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.lower.x < other.lower.x
+    }
+}
+
+impl PartialEq for Bounds {
+    fn eq(&self, other: &Self) -> Option<Ordering> {
+        self.lower.x == other.lower.x
+    }
+}
+*/
+impl Bounds {
+
+    // `p` is where it ended the frame, `d` how far it moved to get there
+    pub fn from(entity:Entity, btype:u8, p:Vector2, d:Vector2, s:Vector2) -> Self {
+        let end = (p - s, p + s);
+        let start = (end.0 - d, end.1 - d);
+        Self {
+            btype,
+            entity,
+            lower: Vector2::from(start.0.x.min(end.0.x), start.0.y.min(end.0.y)),
+            upper: Vector2::from(start.1.x.max(end.1.x), start.1.y.max(end.1.y)),
+            d,
+        }
+    }
+
+    // the box at the start of the frame
+    fn start(&self) -> (Vector2, Vector2) {
+        let lower = Vector2::from(self.lower.x + self.d.x.max(0), self.lower.y + self.d.y.max(0)) - self.d;
+        let upper = Vector2::from(self.upper.x + self.d.x.min(0), self.upper.y + self.d.y.min(0)) - self.d;
+        (lower, upper)
+    }
+
+    // When during the frame the two boxes first touch, from Zero at the start
+    // to One at the end, or None if they never do.  Both move in a straight
+    // line, so a fast body can't skip over a thin one between frames.
+    pub fn sweep(&self, b: &Self) -> Option<Fixed> {
+        let (aLower, aUpper) = self.start();
+        let (bLower, bUpper) = b.start();
+        // a's movement with b held still
+        let v = self.d - b.d;
+
+        // fractions of the frame, numerator over a positive denominator
+        let mut enter = (0i64, 1i64);
+        let mut exit = (1i64, 1i64);
+        let axes = [
+            (v.x, aLower.x, aUpper.x, bLower.x, bUpper.x),
+            (v.y, aLower.y, aUpper.y, bLower.y, bUpper.y),
+        ];
+        for (v, aLower, aUpper, bLower, bUpper) in axes {
+            let (v, aLower, aUpper, bLower, bUpper) = (i64::from(v), i64::from(aLower), i64::from(aUpper), i64::from(bLower), i64::from(bUpper));
+            if v == 0 {
+                if aUpper < bLower || aLower > bUpper {
+                    return None;
+                }
+                continue;
+            }
+            let (near, far) = if v > 0 {
+                (bLower - aUpper, bUpper - aLower)
+            } else {
+                (aLower - bUpper, aUpper - bLower)
+            };
+            let speed = v.abs();
+            if near * enter.1 > enter.0 * speed {
+                enter = (near, speed);
+            }
+            if far * exit.1 < exit.0 * speed {
+                exit = (far, speed);
+            }
+        }
+        if enter.0 * exit.1 > exit.0 * enter.1 {
+            return None;
+        }
+        // 0..=1, so it always fits
+        Some(Fixed::from_bits(i32::try_from((enter.0 << Fixed::FracBits) / enter.1).unwrap()))
+    }
+
+    pub fn less_than(&self, other: &Self) -> bool {
+        self.lower.x < other.lower.x
+    }
+
+    pub fn overlap(&self, b: &Self) -> bool {
+        if b.lower.x > self.upper.x || b.upper.x < self.lower.x ||
+            b.lower.y > self.upper.y || b.upper.y < self.lower.y {
+            return false
+        }
+        true
+    }
+}
+
+// Which ObjType pairs can touch at all, the same both ways round.  Anything
+// not allowed here never gets as far as a contact event.
+#[derive(Default, Copy, Clone, PartialEq)]
+pub struct CollisionMatrix {
+    masks: [u32; ObjType::USizeCount],
+}
+
+impl CollisionMatrix {
+    // nothing collides
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn allow(&mut self, a: u8, b: u8) {
+        self.masks[usize::from(a)] |= 1 << b;
+        self.masks[usize::from(b)] |= 1 << a;
+    }
+
+    pub fn collides(&self, a: u8, b: u8) -> bool {
+        self.masks[usize::from(a)] & (1 << b) != 0
+    }
+
+    // false when the type collides with nothing, so it can be left out entirely
+    pub fn any(&self, a: u8) -> bool {
+        self.masks[usize::from(a)] != 0
+    }
+}
+
+#[derive(Default, Copy, Clone)]
+pub struct Event {
+    pub id: u8,
+    pub a: Entity,
+    pub b: Entity,
+    pub key: u16,
+    pub otype: u8,
+    pub v: FixedVector2,
+    // velocity for Shoot, the prefab's is used otherwise
+    pub d: FixedVector2,
+    // when a Contact happened, see Bounds::sweep
+    pub time: Fixed,
+}
+
+impl Event {
+    pub const Null: u8 = 0;
+    pub const DestroyEntity: u8 = 1;
+    pub const CreateEntity: u8 = 2;
+    pub const Contact: u8 = 3;
+    pub const Shoot: u8 = 4;
+    pub const Count: u8 = 5;
+
+    pub fn from_destroy(a:Entity) -> Self {
+        Self {
+            id: Event::DestroyEntity,
+            a,
+            b: Entity::new(),
+            key: 0,
+            otype: ObjType::Null,
+            v: FixedVector2::new(),
+            d: FixedVector2::new(),
+            time: Fixed::Zero,
+        }
+    }
+
+    pub fn from_contact(key:u16, a:Entity, b:Entity, time:Fixed) -> Self {
+        Self {
+            id: Event::Contact,
+            a,
+            b,
+            key,
+            otype: ObjType::Null,
+            v: FixedVector2::new(),
+            d: FixedVector2::new(),
+            time,
+        }
+    }
+
+    pub fn from_entity(otype: u8, v:FixedVector2) -> Self {
+        Self {
+            id: Event::CreateEntity,
+            a: Entity::new(),
+            b: Entity::new(),
+            key: 0,
+            otype,
+            v,
+            d: FixedVector2::new(),
+            time: Fixed::Zero,
+        }
+    }
+
+    // spawn at `v` moving at `d` instead of the prefab's velocity
+    pub fn from_shot(otype: u8, v:FixedVector2, d:FixedVector2) -> Self {
+        Self {
+            id: Event::Shoot,
+            a: Entity::new(),
+            b: Entity::new(),
+            key: 0,
+            otype,
+            v,
+            d,
+            time: Fixed::Zero,
+        }
+    }
+
+    // `speed` pixels a frame straight at `target`
+    pub fn from_aimed(otype: u8, v:FixedVector2, target:FixedVector2, speed:Fixed) -> Self {
+        Event::from_shot(otype, v, FixedVector2::from_angle((target - v).angle(), speed))
+    }
+
+    // `count` shots `step` apart, fanned out evenly around `centre`
+    pub fn spread(otype: u8, v:FixedVector2, centre:Angle, step:Angle, count:u16, speed:Fixed) -> impl Iterator<Item = Event> {
+        (0..count).map(move |n| {
+            // half steps either side of the centre, so the fan is symmetric
+            let offset = i64::from(step.to_bits()) * (2 * i64::from(n) - i64::from(count) + 1) / 2;
+            let angle = centre + Angle::from_bits(u16::try_from(offset.rem_euclid(65536)).unwrap());
+            Event::from_shot(otype, v, FixedVector2::from_angle(angle, speed))
+        })
+    }
+
+    pub fn from_player(key: u16, v:FixedVector2) -> Self {
+        Self {
+            id: Event::CreateEntity,
+            a: Entity::new(),
+            b: Entity::new(),
+            key,
+            otype: ObjType::Player,
+            v,
+            d: FixedVector2::new(),
+            time: Fixed::Zero,
+        }
+    }
+
+}
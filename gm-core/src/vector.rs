@@ -0,0 +1,63 @@
+use std::ops;
+
+
+/***************************
+
+    Vector2
+
+**************************/
+
+#[derive(Default, Copy, Clone)]
+pub struct Vector2 {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl ops::Neg for Vector2 {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self { x: -self.x, y: -self.y }
+    }
+}
+
+impl ops::Add for Vector2 {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self { x: self.x + other.x, y: self.y + other.y }
+    }
+}
+
+impl ops::Sub for Vector2 {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {x: self.x - other.x, y: self.y - other.y}
+    }
+}
+
+impl ops::AddAssign for Vector2 {
+    fn add_assign(&mut self, other: Self) {
+        self.x += other.x;
+        self.y += other.y;
+    }
+}
+
+impl ops::SubAssign for Vector2 {
+    fn sub_assign(&mut self, other: Self) {
+        self.x -= other.x;
+        self.y -= other.y;
+    }
+}
+
+impl Vector2 {
+    pub fn new() -> Self {
+        Self { x: 0, y: 0 }
+    }
+
+    pub fn from(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+}
+
@@ -0,0 +1,51 @@
+use gm_core::{BitMask, FramePacket, Game, InputLayout};
+
+#[test]
+fn top_bit() {
+    let top = BitMask::from(63, 64);
+    assert_eq!(top.encode(1), i64::MIN);
+    assert_eq!(top.decode(i64::MIN), 1);
+
+    let all = BitMask::from(0, 64);
+    assert_eq!(all.decode(-1), -1);
+    assert_eq!(all.encode(i64::MIN + 5), i64::MIN + 5);
+
+    // unsigned fields that reach bit 63 must not come back negative
+    let high = BitMask::from(48, 64);
+    assert_eq!(high.decode(high.encode(0xffff)), 0xffff);
+}
+
+#[test]
+fn signed_fields() {
+    let x = BitMask::signed(0, 16);
+    for v in [-32768, -960, -1, 0, 1, 959, 32767] {
+        assert_eq!(x.decode(x.encode(v)), v);
+    }
+
+    let y = BitMask::signed(40, 64);
+    for v in [-(1 << 23), -12345, 0, (1 << 23) - 1] {
+        assert_eq!(y.decode(y.encode(v) | 0xff), v);
+    }
+
+    // neighbours are left alone
+    let raw = InputLayout::X.encode(-5) | Game::LeftMask.encode(1) | Game::SyncedMask.encode(1);
+    assert_eq!(Game::XMask.decode(raw), -5);
+    assert_eq!(Game::LeftMask.decode(raw), 1);
+    assert_eq!(Game::SyncedMask.decode(raw), 1);
+}
+
+#[test]
+fn payload_through_the_relay() {
+    let payload: Vec<u8> = (0..=255u8).rev().chain(0..17).collect();
+    let field = InputLayout::Seed.mask;
+    let raws = field.split(&payload);
+    assert_eq!(raws.len(), payload.len().div_ceil(2));
+
+    let mut received = Vec::new();
+    for raw in raws {
+        let mut packet = FramePacket::new();
+        packet.raw[3] = raw | Game::SyncedMask.encode(1);
+        received.push(FramePacket::decode(&packet.encode()).unwrap().raw[3]);
+    }
+    assert_eq!(field.join(&received, payload.len()), payload);
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use gm_core::{Bounds, Broadphase, CollisionMatrix, Data, Entity, Game, MersenneTwister, ObjType, Vector2};
+
+fn random_bounds(rand: &mut MersenneTwister, count: usize) -> Vec<Bounds> {
+    let mut bounds: Vec<Bounds> = (0..count).map(|n| {
+        let p = Vector2::from(rand.next_range(-1000, 1000), rand.next_range(-600, 600));
+        // mostly small, a few huge like the shot cleaners
+        let s = if n % 50 == 0 {
+            Vector2::from(960, 540)
+        } else {
+            Vector2::from(rand.next_range(0, 40), rand.next_range(0, 40))
+        };
+        let btype = u8::try_from(n % ObjType::USizeCount).unwrap();
+        Bounds::from(Entity::from_index(n), btype, p, Vector2::new(), s)
+    }).collect();
+    bounds.sort_by_key(|b| b.lower.x);
+    bounds
+}
+
+fn everything() -> CollisionMatrix {
+    let mut matrix = CollisionMatrix::new();
+    for a in 0..ObjType::Count {
+        for b in 0..ObjType::Count {
+            matrix.allow(a, b);
+        }
+    }
+    matrix
+}
+
+fn pairs(broadphase: Broadphase, bounds: &[Bounds], matrix: &CollisionMatrix) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    broadphase.pairs(bounds, matrix, &mut pairs, &mut Vec::new());
+    pairs
+}
+
+#[test]
+fn same_pairs_as_brute_force() {
+    let mut rand = MersenneTwister::from(42);
+    for count in [0, 1, 2, 10, 300, 700] {
+        let bounds = random_bounds(&mut rand, count);
+        for matrix in [everything(), Data::new().collisions] {
+            let mut expected = Vec::new();
+            for i in 0..bounds.len() {
+                for j in i + 1..bounds.len() {
+                    if matrix.collides(bounds[i].btype, bounds[j].btype) && bounds[i].overlap(&bounds[j]) {
+                        expected.push((i, j));
+                    }
+                }
+            }
+            assert_eq!(pairs(Broadphase::SweepAndPrune, &bounds, &matrix), expected);
+            assert_eq!(pairs(Broadphase::Grid, &bounds, &matrix), expected);
+        }
+    }
+}
+
+// touching edges count, even when the edge is a cell boundary
+#[test]
+fn touching_on_a_cell_edge() {
+    let cell = Broadphase::CellSize;
+    let a = Bounds::from(Entity::from_index(0), 0, Vector2::from(cell - 8, 0), Vector2::new(), Vector2::from(8, 8));
+    let b = Bounds::from(Entity::from_index(1), 0, Vector2::from(cell + 8, 16), Vector2::new(), Vector2::from(8, 8));
+    assert_eq!(pairs(Broadphase::Grid, &[a, b], &everything()), [(0, 1)]);
+    assert_eq!(pairs(Broadphase::SweepAndPrune, &[a, b], &everything()), [(0, 1)]);
+}
+
+#[test]
+fn collision_matrix() {
+    let matrix = Data::new().collisions;
+    assert!(matrix.collides(ObjType::Bullet, ObjType::Enemy));
+    assert!(matrix.collides(ObjType::Enemy, ObjType::Bullet));
+    assert!(matrix.collides(ObjType::Player, ObjType::BadBullet));
+    assert!(!matrix.collides(ObjType::Enemy, ObjType::Enemy));
+    assert!(!matrix.collides(ObjType::Player, ObjType::Player));
+    assert!(!matrix.collides(ObjType::Bullet, ObjType::Player));
+    assert!(!matrix.any(ObjType::Boom));
+
+    // a pair the matrix rules out is never reported, however much it overlaps
+    let enemy = |n| Bounds::from(Entity::from_index(n), ObjType::Enemy, Vector2::new(), Vector2::new(), Vector2::from(16, 10));
+    for broadphase in [Broadphase::SweepAndPrune, Broadphase::Grid] {
+        assert!(pairs(broadphase, &[enemy(0), enemy(1)], &matrix).is_empty());
+    }
+}
+
+fn play(broadphase: Broadphase) -> Vec<u64> {
+    let mut game = Game::new();
+    game.slots.copy_from_slice(&common::players(3));
+    game.broadphase = broadphase;
+    game.init(77);
+
+    let mut rand = MersenneTwister::from(3);
+    let mut checksums = Vec::new();
+    for _ in 0..900 {
+        common::press(&mut game.slots, 3, &mut rand);
+        for slot in game.slots.iter_mut().take(3) {
+            slot.raw |= Game::PrimaryMask.encode(1);
+        }
+        game.update();
+        checksums.push(game.checksum());
+    }
+    // shots hit something, or this proves nothing
+    assert!(game.global.enemyCount < 480);
+    checksums
+}
+
+#[test]
+fn games_match_frame_for_frame() {
+    assert!(play(Broadphase::SweepAndPrune) == play(Broadphase::Grid));
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use gm_core::{BrokenInput, Game, MersenneTwister, NetConditions, NetSim, Replay, RollbackSession};
+
+fn start(policy: BrokenInput) -> Game {
+    let mut game = Game::new();
+    game.slots.copy_from_slice(&common::players(2));
+    game.brokenInput = policy;
+    game.init(5);
+    game
+}
+
+// slot 0 holds left and fire, then its next frame is dropped while the relay
+// repeats something else entirely
+fn drop_a_frame(game: &mut Game) {
+    let synced = Game::SyncedMask.encode(1);
+    game.slots[0].raw = Game::LeftMask.encode(1) | Game::PrimaryMask.encode(1) | synced;
+    game.slots[1].raw = synced;
+    game.update();
+
+    game.slots[0].raw = Game::RightMask.encode(1) | synced;
+    game.slots[0].broken = true;
+    game.update();
+}
+
+#[test]
+fn hold_keeps_last_input() {
+    let mut game = start(BrokenInput::Hold);
+    drop_a_frame(&mut game);
+    let input = game.slots[0].input;
+    assert!(input.left && input.primary);
+    assert!(!input.right);
+}
+
+#[test]
+fn neutral_lets_go() {
+    let mut game = start(BrokenInput::Neutral);
+    drop_a_frame(&mut game);
+    let input = game.slots[0].input;
+    assert!(!input.left && !input.right && !input.primary);
+}
+
+#[test]
+fn policy_is_part_of_the_state() {
+    assert_ne!(start(BrokenInput::Hold).checksum(), start(BrokenInput::Neutral).checksum());
+
+    let mut game = start(BrokenInput::Hold);
+    game.load_snapshot(&start(BrokenInput::Neutral).save_snapshot()).unwrap();
+    assert!(game.brokenInput == BrokenInput::Neutral);
+
+    let mut replay = Replay::new(5, &game.slots);
+    replay.brokenInput = BrokenInput::Neutral;
+    let replay = Replay::decode(&replay.encode()).unwrap();
+    assert!(replay.start().brokenInput == BrokenInput::Neutral);
+}
+
+#[test]
+fn every_client_agrees() {
+    let conditions = [
+        NetConditions { latency: 30, jitter: 20, loss: 10, dropped: 20 },
+        NetConditions { latency: 60, jitter: 0, loss: 0, dropped: 30 },
+        NetConditions::perfect(),
+    ];
+    let mut runs = Vec::new();
+    for policy in [BrokenInput::Hold, BrokenInput::Neutral] {
+        let mut sim = NetSim::new(31, &conditions);
+        for client in &mut sim.clients {
+            client.game.brokenInput = policy;
+        }
+        sim.run(8_000);
+        assert!(sim.confirmed_frames() > 100);
+        assert_eq!(sim.first_mismatch(), None);
+        runs.push(sim.clients[0].checksums.clone());
+    }
+    // and the policy actually changed what happened
+    assert_ne!(runs[0], runs[1]);
+}
+
+// a new match must not hold on to the last one's input
+#[test]
+fn init_lets_go() {
+    let mut game = start(BrokenInput::Hold);
+    drop_a_frame(&mut game);
+    game.init(5);
+    let input = game.slots[0].input;
+    assert!(!input.left && !input.right && !input.primary);
+
+    // so a dropped first frame plays out like it does in a fresh game
+    let mut fresh = start(BrokenInput::Hold);
+    for g in [&mut game, &mut fresh] {
+        g.slots[0].raw = Game::SyncedMask.encode(1);
+        g.slots[0].broken = true;
+        g.slots[1].raw = Game::SyncedMask.encode(1);
+        g.update();
+    }
+    assert_eq!(game.checksum(), fresh.checksum());
+}
+
+// remote slot 1 drops every few frames while the relay repeats something else,
+// the confirmed rollback state has to hold what lockstep holds
+#[test]
+fn rollback_holds_like_lockstep() {
+    let players = common::players(2);
+    let mut lockstep = Game::new();
+    lockstep.slots.copy_from_slice(&players);
+    lockstep.brokenInput = BrokenInput::Hold;
+    lockstep.init(5);
+    let mut session = RollbackSession::new(0, RollbackSession::DefaultCapacity);
+    session.game_mut().brokenInput = BrokenInput::Hold;
+    session.init(5, &players);
+
+    let mut rand = MersenneTwister::from(3);
+    let synced = Game::SyncedMask.encode(1);
+    for frame in 1..=300 {
+        while session.head_frame() < frame + 3 {
+            session.predict(synced | Game::PrimaryMask.encode(1));
+        }
+        let mut slots = players.clone();
+        slots[0].raw = synced | Game::PrimaryMask.encode(1);
+        slots[1].raw = common::keys(&mut rand);
+        slots[1].broken = rand.next_u32().is_multiple_of(3);
+
+        common::receive(&mut lockstep, &slots);
+        lockstep.update();
+        session.confirm(&slots);
+        assert_eq!(session.confirmed_game().checksum(), lockstep.checksum(), "frame {}", frame);
+    }
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use gm_core::{Cf, Checksum, Fixed, FixedVector2, Game, MersenneTwister};
+
+fn played(frames: u32) -> Game {
+    let mut game = common::start(3, 11);
+    common::play(&mut game, 3, &mut MersenneTwister::from(8), frames);
+    game
+}
+
+// FNV-1a, so the published test vectors apply
+#[test]
+fn known_values() {
+    assert_eq!(Checksum::new().value(), 0xcbf29ce484222325);
+    let mut sum = Checksum::new();
+    sum.write_bytes(b"a");
+    assert_eq!(sum.value(), 0xaf63dc4c8601ec8c);
+    let mut sum = Checksum::new();
+    sum.write_bytes(b"foobar");
+    assert_eq!(sum.value(), 0x85944171f73967e8);
+}
+
+#[test]
+fn stable_across_smart_copy() {
+    let game = played(400);
+    assert_eq!(game.checksum(), game.checksum());
+
+    let mut copy = Game::new();
+    copy.smartCopy(&game);
+    assert_eq!(copy.checksum(), game.checksum());
+
+    // and the copy carries on exactly like the original
+    let mut original = played(400);
+    for _ in 0..100 {
+        original.update();
+        copy.update();
+    }
+    assert_eq!(copy.checksum(), original.checksum());
+}
+
+#[test]
+fn any_change_shows() {
+    let game = played(400);
+    let base = game.checksum();
+    let changes: [fn(&mut Game); 8] = [
+        |g| g.frame += 1,
+        |g| g.gameOver = !g.gameOver,
+        |g| g.global.enemyCount += 1,
+        |g| { g.rand.next_u32(); },
+        |g| g.slots[5].raw ^= 1,
+        |g| g.slots[0].input.left = !g.slots[0].input.left,
+        |g| {
+            let r = g.components.filter(Cf::Active | Cf::Body).next().unwrap();
+            r.body.position += FixedVector2::from(Fixed::from_bits(1), Fixed::Zero);
+        },
+        |g| {
+            let r = g.components.filter(Cf::Active | Cf::Animator).last().unwrap();
+            r.animator.count += 1;
+        },
+    ];
+
+    for (n, change) in changes.iter().enumerate() {
+        let mut copy = Game::new();
+        copy.smartCopy(&game);
+        change(&mut copy);
+        assert_ne!(copy.checksum(), base, "change {} went unnoticed", n);
+    }
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use std::rc::Rc;
+
+use gm_core::{Cf, CollisionHandler, CpReference, Data, Event, FixedVector2, Game, MersenneTwister, ObjType};
+use gm_core::{Replay, ReplayPlayer, RollbackSession};
+
+// a game with nothing but what `spawn` puts in it, all on the same spot
+fn touching(spawn: &[u8]) -> Game {
+    let mut game = Game::new();
+    game.init(1);
+    for otype in spawn {
+        game.eventList.push(Event::from_entity(*otype, FixedVector2::new()));
+    }
+    game.resolveEvents();
+    game
+}
+
+fn rules(a: u8, b: u8, handler: CollisionHandler) -> Rc<Data> {
+    let mut data = Data::new();
+    data.registerCollision(a, b, handler);
+    Rc::new(data)
+}
+
+fn contact(game: &mut Game) {
+    game.fillContactList();
+    game.resolveEvents();
+}
+
+fn frame_of(game: &mut Game, otype: u8) -> u16 {
+    game.components.filter(Cf::Active | Cf::Body)
+        .find(|r| r.objectId.0 == otype)
+        .map(|r| r.animator.frame)
+        .unwrap()
+}
+
+fn mark_player(player: &mut CpReference, enemy: &mut CpReference, _events: &mut Vec<Event>) {
+    assert_eq!(player.objectId.0, ObjType::Player);
+    assert_eq!(enemy.objectId.0, ObjType::Enemy);
+    player.player.damage = 7;
+}
+
+fn mark_enemy(enemy: &mut CpReference, bullet: &mut CpReference, _events: &mut Vec<Event>) {
+    assert_eq!(enemy.objectId.0, ObjType::Enemy);
+    assert_eq!(bullet.objectId.0, ObjType::Bullet);
+    enemy.animator.frame = Data::target;
+}
+
+#[test]
+fn built_in_handlers() {
+    let mut game = touching(&[ObjType::Bullet, ObjType::Enemy]);
+    contact(&mut game);
+    assert_eq!(frame_of(&mut game, ObjType::Enemy), Data::_null);
+    assert_eq!(frame_of(&mut game, ObjType::Boom), Data::enemy_boom_0);
+
+    // no handler, so nothing happens and nothing is even reported
+    let mut game = touching(&[ObjType::Enemy, ObjType::Enemy]);
+    game.fillContactList();
+    assert!(game.eventList.is_empty());
+}
+
+#[test]
+fn a_new_pair_is_one_registration() {
+    let mut game = touching(&[ObjType::Enemy, ObjType::Player]);
+    game.fillContactList();
+    assert!(game.eventList.is_empty());
+
+    // registered lower type first, the handler still gets the player first
+    game.data = rules(ObjType::Player, ObjType::Enemy, mark_player);
+    contact(&mut game);
+    let damage = game.components.filter(Cf::Active | Cf::Player).next().unwrap().player.damage;
+    assert_eq!(damage, 7);
+}
+
+#[test]
+fn registering_again_replaces() {
+    let mut game = touching(&[ObjType::Bullet, ObjType::Enemy]);
+    game.data = rules(ObjType::Enemy, ObjType::Bullet, mark_enemy);
+    contact(&mut game);
+    assert_eq!(frame_of(&mut game, ObjType::Enemy), Data::target);
+    assert_eq!(frame_of(&mut game, ObjType::Bullet), Data::player_shot);
+}
+
+// shots go straight through, so the enemies never die
+fn pass_through(_enemy: &mut CpReference, _bullet: &mut CpReference, _events: &mut Vec<Event>) {}
+
+// every game that starts from a recording or a copy plays by the same rules
+#[test]
+fn registrations_reach_every_copy() {
+    let data = rules(ObjType::Enemy, ObjType::Bullet, pass_through);
+    let mut game = Game::with_data(Rc::clone(&data));
+    game.slots.copy_from_slice(&common::players(2));
+    game.init(8);
+    let mut session = RollbackSession::new(0, RollbackSession::DefaultCapacity);
+    session.game_mut().data = Rc::clone(&data);
+    session.init(8, &common::players(2));
+    let mut replay = Replay::new(8, &game.slots);
+    replay.keyframeInterval = 128;
+
+    let mut rand = MersenneTwister::from(6);
+    let mut slots = common::players(2);
+    let mut sums = vec![game.checksum()];
+    for frame in 1..=300 {
+        while session.head_frame() < frame + 3 {
+            session.predict(slots[0].raw);
+        }
+        common::press(&mut slots, 2, &mut rand);
+        slots[0].raw |= Game::PrimaryMask.encode(1);
+        common::receive(&mut game, &slots);
+        game.update();
+        replay.record(&game);
+        sums.push(game.checksum());
+        session.confirm(&slots);
+        assert_eq!(session.confirmed_game().checksum(), game.checksum(), "frame {}", frame);
+    }
+
+    let mut player = ReplayPlayer::with_data(replay.clone(), Rc::clone(&data));
+    player.seek(300).unwrap();
+    assert_eq!(player.game().checksum(), sums[300]);
+    // back past every keyframe, to a game the player had to start over
+    player.seek(50).unwrap();
+    assert_eq!(player.game().checksum(), sums[50]);
+    player.seek(250).unwrap();
+    assert_eq!(player.game().checksum(), sums[250]);
+
+    // a forward copy starts from any old game
+    let mut forward = Game::new();
+    forward.smartCopy(player.game());
+    assert!(Rc::ptr_eq(&forward.data, &data));
+
+    // and the handler mattered, while there were enemies to shoot
+    let mut plain = ReplayPlayer::new(replay);
+    plain.seek(250).unwrap();
+    assert_ne!(plain.game().checksum(), sums[250]);
+}
@@ -0,0 +1,54 @@
+// Fixtures shared by the integration tests.  Every test file compiles its own
+// copy and only uses some of it.
+#![allow(dead_code)]
+
+use gm_core::{Game, MersenneTwister, Slot};
+
+// the first `count` slots, connected since the start of the match
+pub fn players(count: usize) -> Vec<Slot> {
+    let mut slots = vec![Slot::default(); Game::SlotCount];
+    for slot in slots.iter_mut().take(count) {
+        slot.connected = true;
+        slot.connectedAtStart = true;
+    }
+    slots
+}
+
+// a match with `count` players, right after init
+pub fn start(count: usize, seed: u32) -> Game {
+    let mut game = Game::new();
+    game.slots.copy_from_slice(&players(count));
+    game.init(seed);
+    game
+}
+
+// synced, with random left, right and fire
+pub fn keys(rand: &mut MersenneTwister) -> i64 {
+    let keys = rand.next_u32();
+    Game::SyncedMask.encode(1)
+        | Game::LeftMask.encode(i64::from(keys & 1))
+        | Game::RightMask.encode(i64::from((keys >> 1) & 1))
+        | Game::PrimaryMask.encode(i64::from((keys >> 2) & 1))
+}
+
+// new keys for the first `count` slots
+pub fn press(slots: &mut [Slot], count: usize, rand: &mut MersenneTwister) {
+    for slot in slots.iter_mut().take(count) {
+        slot.raw = keys(rand);
+    }
+}
+
+// `frames` confirmed updates with random keys for the first `count` slots
+pub fn play(game: &mut Game, count: usize, rand: &mut MersenneTwister, frames: u32) {
+    for _ in 0..frames {
+        press(&mut game.slots, count, rand);
+        game.update();
+    }
+}
+
+// what the relay sent for a frame, keeping the input a dropped frame holds
+pub fn receive(game: &mut Game, slots: &[Slot]) {
+    for (slot, input) in game.slots.iter_mut().zip(slots) {
+        slot.receive(input);
+    }
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use gm_core::{Desync, Game, Slot};
+
+fn connected(raw: i64) -> Slot {
+    Slot { connected: true, raw, ..Default::default() }
+}
+
+#[test]
+fn stamps_round_trip_through_raw() {
+    let keys = Game::SyncedMask.encode(1) | Game::LeftMask.encode(1) | Game::XMask.encode(-300);
+    let mut desync = Desync::new();
+    // nothing recorded yet, nothing stamped
+    assert_eq!(desync.stamp(keys), keys);
+
+    desync.record(64, 0x1234_5678_9abc_def0);
+    let raw = desync.stamp(keys);
+    assert_eq!(Desync::FlagMask.decode(raw), 1);
+    assert_eq!(Desync::FrameMask.decode(raw), 2);
+    assert_eq!(Desync::SumMask.decode(raw), 0xbc_def0);
+    // the game only sees its own fields
+    assert_eq!(Game::LeftMask.decode(raw), 1);
+    assert_eq!(Game::XMask.decode(raw), -300);
+    assert_eq!(Game::SyncedMask.decode(raw), 1);
+
+    // restamping replaces the old stamp instead of mixing with it
+    desync.record(96, 0xffff_ffff);
+    let again = desync.stamp(raw);
+    assert_eq!(Desync::FrameMask.decode(again), 3);
+    assert_eq!(Desync::SumMask.decode(again), 0xff_ffff);
+    assert_eq!(again & !(Desync::FlagMask.mask | Desync::FrameMask.mask | Desync::SumMask.mask), keys);
+}
+
+// what another client would stamp, having recorded `sums` at checkpoints 32, 64, ...
+fn stamped(sums: &[u64]) -> i64 {
+    let mut desync = Desync::new();
+    for (n, sum) in sums.iter().enumerate() {
+        desync.record((u32::try_from(n).unwrap() + 1) * Desync::Interval, *sum);
+    }
+    desync.stamp(0)
+}
+
+#[test]
+fn flags_the_first_mismatch() {
+    let mut ours = Desync::new();
+    for sum in [1, 2, 3] {
+        ours.record(sum * Desync::Interval, u64::from(sum));
+    }
+
+    // matching stamps and stamps from disconnected slots are fine
+    let mut stranger = connected(stamped(&[9]));
+    stranger.connected = false;
+    ours.check(&[connected(stamped(&[1])), connected(stamped(&[1, 2, 3])), stranger]);
+    assert!(!ours.desynced());
+
+    // stamps arrive late and out of order, the earliest bad checkpoint wins
+    ours.check(&[connected(0), connected(stamped(&[1, 2, 9]))]);
+    assert_eq!(ours.first_frame(), Some(96));
+    assert_eq!(ours.first_slot(), Some(1));
+    ours.check(&[connected(0), connected(0), connected(stamped(&[1, 9]))]);
+    assert_eq!(ours.first_frame(), Some(64));
+    assert_eq!(ours.first_slot(), Some(2));
+    ours.check(&[connected(stamped(&[1, 2, 9, 9]))]);
+    assert_eq!(ours.first_frame(), Some(64));
+
+    ours.reset();
+    assert!(!ours.desynced());
+}
+
+// two games trading stamped inputs, one of them goes wrong on frame 40
+#[test]
+fn games_catch_the_right_checkpoint() {
+    let mut games = [common::start(2, 3), common::start(2, 3)];
+
+    for frame in 1..=100 {
+        let keys = Game::SyncedMask.encode(1) | Game::LeftMask.encode(i64::from(frame % 3 == 0));
+        let raws = [games[0].desync.stamp(keys), games[1].desync.stamp(keys)];
+        for game in &mut games {
+            game.slots[0].raw = raws[0];
+            game.slots[1].raw = raws[1];
+            game.update();
+        }
+        if frame == 40 {
+            games[1].rand.next_u32();
+        }
+    }
+
+    assert_eq!(games[0].desync.first_frame(), Some(64));
+    assert_eq!(games[0].desync.first_slot(), Some(1));
+    assert_eq!(games[1].desync.first_frame(), Some(64));
+    assert_eq!(games[1].desync.first_slot(), Some(0));
+}
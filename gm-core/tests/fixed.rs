@@ -0,0 +1,67 @@
+use gm_core::{Fixed, FixedVector2, Vector2};
+
+#[test]
+fn conversions() {
+    assert_eq!(Fixed::from_int(3).to_int(), 3);
+    assert_eq!(Fixed::from_int(-960).to_int(), -960);
+    assert_eq!(Fixed::from_ratio(7, 2).to_bits(), 0x3_8000);
+    assert_eq!(Fixed::from_ratio(-1, 2).to_int(), -1);
+    assert_eq!(Fixed::from_ratio(-1, 2).round(), 0);
+    assert_eq!(Fixed::from_ratio(5, 2).round(), 3);
+    assert_eq!(Fixed::from_ratio(1, 4).to_f32(), 0.25);
+
+    // whole numbers outside 16 bits saturate instead of wrapping
+    assert_eq!(Fixed::from_int(40_000), Fixed::Max);
+    assert_eq!(Fixed::from_int(-40_000), Fixed::Min);
+
+    let v = FixedVector2::from(Fixed::from_ratio(-3, 2), Fixed::from_ratio(3, 2));
+    let p = v.to_pixels();
+    assert_eq!((p.x, p.y), (-2, 1));
+    assert_eq!(FixedVector2::from_pixels(Vector2::from(4, -5)), FixedVector2::from_int(4, -5));
+}
+
+#[test]
+fn arithmetic_rounds_down() {
+    let third = Fixed::One / Fixed::from_int(3);
+    assert_eq!(third.to_bits(), 0x5555);
+    assert_eq!((-Fixed::One / Fixed::from_int(3)).to_bits(), -0x5556);
+    assert_eq!((Fixed::One / Fixed::from_int(-3)).to_bits(), -0x5556);
+    assert_eq!((Fixed::from_ratio(3, 2) * Fixed::from_ratio(3, 2)), Fixed::from_ratio(9, 4));
+    assert_eq!((Fixed::from_bits(-1) * Fixed::from_ratio(1, 2)).to_bits(), -1);
+    assert_eq!(Fixed::from_ratio(1, 2) * 3, Fixed::from_ratio(3, 2));
+}
+
+// a third of a pixel a frame adds up to exactly a pixel every three frames
+#[test]
+fn sub_pixel_movement() {
+    let mut position = FixedVector2::from_int(10, 0);
+    let velocity = FixedVector2::from(Fixed::from_ratio(1, 3), Fixed::from_ratio(-1, 3));
+    let mut seen = Vec::new();
+    for _ in 0..6 {
+        position += velocity;
+        seen.push(position.to_pixels().x);
+    }
+    assert_eq!(seen, [10, 10, 10, 11, 11, 11]);
+    assert_eq!(position.y.round(), -2);
+}
+
+#[test]
+fn saturates_and_checks() {
+    let big = Fixed::from_int(30_000);
+    assert_eq!(big + big, Fixed::Max);
+    assert_eq!(-big - big, Fixed::Min);
+    assert_eq!(big * Fixed::from_int(2), Fixed::Max);
+    assert_eq!(big * Fixed::from_int(-2), Fixed::Min);
+    assert_eq!(big * 2, Fixed::Max);
+    assert_eq!(-Fixed::Min, Fixed::Max);
+    assert_eq!(Fixed::One / Fixed::Zero, Fixed::Max);
+    assert_eq!(-Fixed::One / Fixed::Zero, Fixed::Min);
+    assert_eq!(Fixed::Zero / Fixed::Zero, Fixed::Zero);
+
+    assert_eq!(big.checked_add(big), None);
+    assert_eq!(big.checked_sub(-big), None);
+    assert_eq!(big.checked_mul(Fixed::from_int(2)), None);
+    assert_eq!(Fixed::One.checked_div(Fixed::Zero), None);
+    assert_eq!(big.checked_div(Fixed::from_ratio(1, 2)), None);
+    assert_eq!(Fixed::One.checked_add(Fixed::One), Some(Fixed::from_int(2)));
+}
@@ -0,0 +1,75 @@
+#![allow(non_upper_case_globals)]
+
+use gm_core::{InputField, InputLayout, LayoutError};
+
+fn layout(fields: &'static [InputField]) -> Result<(), LayoutError> {
+    InputLayout { name: "test", fields }.validate()
+}
+
+const Overlapping: [InputField; 4] = [
+    InputLayout::Synced,
+    InputField::new("a", 0, 8),
+    InputField::new("b", 8, 12),
+    InputField::new("c", 11, 16),
+];
+
+const TooHigh: [InputField; 3] = [
+    InputField::new("a", 0, 8),
+    InputField::new("top", 56, 57),
+    InputLayout::Synced,
+];
+
+const NoBits: [InputField; 3] = [
+    InputLayout::Synced,
+    InputField::new("a", 0, 8),
+    InputField::new("none", 8, 8),
+];
+
+const Backwards: [InputField; 2] = [
+    InputField::new("a", 9, 8),
+    InputLayout::Synced,
+];
+
+// synced has to be exactly at 55
+const Unsynced: [InputField; 2] = [
+    InputField::new("a", 0, 8),
+    InputField::new("synced", 54, 55),
+];
+
+const Wide: [InputField; 2] = [
+    InputField::new("a", 0, 8),
+    InputField::new("synced", 54, 56),
+];
+
+const Fine: [InputField; 3] = [
+    InputField::new("a", 0, 8),
+    InputField::signed("b", 8, 55),
+    InputLayout::Synced,
+];
+
+#[test]
+fn shipped_layouts_are_valid() {
+    for layout in InputLayout::Layouts {
+        assert_eq!(layout.validate(), Ok(()), "{}", layout.name);
+    }
+}
+
+#[test]
+fn bad_layouts_are_caught() {
+    assert_eq!(layout(&Overlapping), Err(LayoutError::Overlap(2, 3)));
+    assert_eq!(layout(&TooHigh), Err(LayoutError::OutOfRange(1)));
+    assert_eq!(layout(&NoBits), Err(LayoutError::Empty(2)));
+    assert_eq!(layout(&Backwards), Err(LayoutError::Empty(0)));
+    assert_eq!(layout(&Unsynced), Err(LayoutError::NoSynced));
+    // covers synced, but also the bit below it
+    assert_eq!(layout(&Wide), Err(LayoutError::NoSynced));
+    assert_eq!(layout(&[]), Err(LayoutError::NoSynced));
+    assert_eq!(layout(&Fine), Ok(()));
+}
+
+#[test]
+fn errors_name_the_fields() {
+    assert_eq!(LayoutError::Overlap(2, 3).to_string(), "fields 2 and 3 overlap");
+    assert_eq!(LayoutError::OutOfRange(1).to_string(), "field 1 does not fit in 56 bits");
+    assert_eq!(LayoutError::NoSynced.to_string(), "synced is not at bits 55..56");
+}
@@ -0,0 +1,201 @@
+mod common;
+
+use gm_core::{Cf, FramePacket, Game, JoinSnapshot, LateJoin, MersenneTwister, SnapshotError};
+
+// what the relay broadcasts: slots 0 and 1 from the start, slot 2 connects on
+// frame 120 and syncs a few frames later
+fn packet(frame: u32, rand: &mut MersenneTwister) -> Vec<u8> {
+    let mut packet = FramePacket::new();
+    for n in 0..3 {
+        if n == 2 && frame < 120 {
+            continue;
+        }
+        packet.connected |= 1 << n;
+        let synced = i64::from(n < 2 || frame >= 125);
+        packet.raw[n] = common::keys(rand) & !Game::SyncedMask.mask | Game::SyncedMask.encode(synced);
+    }
+    packet.encode()
+}
+
+fn host() -> Game {
+    common::start(2, 40)
+}
+
+#[test]
+fn joiner_matches_the_host() {
+    let mut host = host();
+    let mut rand = MersenneTwister::from(9);
+    // the joiner only decodes packets until it has the state
+    let mut joiner = Game::new();
+    let mut join = LateJoin::new(2);
+    let mut adopted = false;
+    let mut snapshot = None;
+
+    for frame in 1..=200 {
+        let bytes = packet(frame, &mut rand);
+        host.read_packet(&bytes).unwrap();
+        host.update();
+        if host.joined != 0 {
+            assert_eq!(host.joined, 1 << 2);
+            assert_eq!(JoinSnapshot::sender(&host), Some(0));
+            snapshot = Some(JoinSnapshot::from_game(&host).encode());
+        }
+
+        if frame < 120 {
+            continue;
+        }
+        joiner.read_packet(&bytes).unwrap();
+        if adopted {
+            joiner.update();
+            assert_eq!(joiner.checksum(), host.checksum());
+            continue;
+        }
+        join.buffer(&joiner.slots);
+        // the snapshot takes a while to get here
+        if frame == 140 {
+            adopted = join.adopt(&mut joiner, snapshot.as_ref().unwrap()).unwrap();
+            assert!(adopted);
+            assert_eq!(joiner.checksum(), host.checksum());
+        }
+    }
+    assert_eq!(joiner.checksum(), host.checksum());
+}
+
+#[test]
+fn waits_for_its_join_frame() {
+    let mut host = host();
+    let mut rand = MersenneTwister::from(9);
+    let mut joiner = Game::new();
+    let mut early = LateJoin::new(2);
+    let mut snapshot = Vec::new();
+
+    for frame in 1..=130 {
+        let bytes = packet(frame, &mut rand);
+        host.read_packet(&bytes).unwrap();
+        host.update();
+        if host.joined != 0 {
+            snapshot = JoinSnapshot::from_game(&host).encode();
+        }
+        // only what arrived before slot 2 synced
+        if (120..125).contains(&frame) {
+            joiner.read_packet(&bytes).unwrap();
+            early.buffer(&joiner.slots);
+        }
+    }
+
+    let before = joiner.checksum();
+    assert_eq!(early.adopt(&mut joiner, &snapshot), Ok(false));
+    assert_eq!(joiner.checksum(), before);
+
+    // somebody else's snapshot, or not a snapshot at all
+    assert_eq!(LateJoin::new(3).adopt(&mut joiner, &snapshot), Err(SnapshotError::Invalid("snapshot is for another slot")));
+    assert_eq!(early.adopt(&mut joiner, &host.save_snapshot()), Err(SnapshotError::BadMagic));
+    assert_eq!(early.adopt(&mut joiner, &snapshot[..2]), Err(SnapshotError::BadMagic));
+    assert_eq!(early.adopt(&mut joiner, &snapshot[..14]), Err(SnapshotError::Truncated));
+}
+
+#[test]
+fn never_guesses_the_join_frame() {
+    let mut host = host();
+    let mut rand = MersenneTwister::from(9);
+    let mut joiner = Game::new();
+    let mut late = LateJoin::new(2);
+    let mut snapshot = None;
+
+    for frame in 1..=140 {
+        let bytes = packet(frame, &mut rand);
+        host.read_packet(&bytes).unwrap();
+        host.update();
+        if host.joined != 0 {
+            snapshot = Some(JoinSnapshot::from_game(&host));
+        }
+        // the frame slot 2 synced on never arrived, later ones look just like it
+        if frame > 125 {
+            joiner.read_packet(&bytes).unwrap();
+            late.buffer(&joiner.slots);
+        }
+    }
+    let mut snapshot = snapshot.unwrap();
+    assert_eq!(snapshot.frame, host.frame - 15);
+    assert_eq!(late.adopt(&mut joiner, &snapshot.encode()), Ok(false));
+
+    // the state has to be of the frame the snapshot names
+    let mut rand = MersenneTwister::from(9);
+    let mut early = LateJoin::new(2);
+    for frame in 1..=125 {
+        let bytes = packet(frame, &mut rand);
+        if frame >= 120 {
+            joiner.read_packet(&bytes).unwrap();
+            early.buffer(&joiner.slots);
+        }
+    }
+    snapshot.frame += 1;
+    assert_eq!(early.adopt(&mut joiner, &snapshot.encode()), Err(SnapshotError::Invalid("snapshot is not of its join frame")));
+}
+
+#[test]
+fn gives_up_without_a_snapshot() {
+    let mut rand = MersenneTwister::from(9);
+    let mut joiner = Game::new();
+    let mut join = LateJoin::new(2);
+    for frame in 0..LateJoin::Timeout as u32 {
+        joiner.read_packet(&packet(120 + frame, &mut rand)).unwrap();
+        join.buffer(&joiner.slots);
+        assert!(!join.timed_out());
+    }
+    joiner.read_packet(&packet(120, &mut rand)).unwrap();
+    join.buffer(&joiner.slots);
+    assert!(join.timed_out());
+}
+
+// slot 2 only watches: it asks for the state once on frame 125, and its
+// upload for frame 126 is late so the relay repeats the one that asked
+fn watched(frame: u32, rand: &mut MersenneTwister) -> Vec<u8> {
+    let mut packet = FramePacket::decode(&packet(frame, rand)).unwrap();
+    if frame >= 120 {
+        packet.raw[2] = Game::WatchMask.encode(i64::from(frame == 125 || frame == 126));
+        if frame == 126 {
+            packet.dropped |= 1 << 2;
+        }
+    }
+    packet.encode()
+}
+
+#[test]
+fn spectator_matches_the_host() {
+    let mut host = host();
+    let mut rand = MersenneTwister::from(9);
+    let mut spectator = Game::new();
+    let mut join = LateJoin::new(2);
+    let mut snapshot = None;
+    let mut adopted = false;
+
+    for frame in 1..=200 {
+        let bytes = watched(frame, &mut rand);
+        host.read_packet(&bytes).unwrap();
+        host.update();
+        // once, and without a ship
+        assert_eq!(host.joined, if frame == 125 { 1 << 2 } else { 0 }, "frame {}", frame);
+        if host.joined != 0 {
+            assert_eq!(JoinSnapshot::sender(&host), Some(0));
+            snapshot = Some(JoinSnapshot::from_game(&host).encode());
+        }
+
+        if frame < 120 {
+            continue;
+        }
+        spectator.read_packet(&bytes).unwrap();
+        if adopted {
+            spectator.update();
+            assert_eq!(spectator.checksum(), host.checksum());
+            continue;
+        }
+        join.buffer(&spectator.slots);
+        if frame == 135 {
+            adopted = join.adopt(&mut spectator, snapshot.as_ref().unwrap()).unwrap();
+            assert!(adopted);
+        }
+    }
+    assert_eq!(spectator.checksum(), host.checksum());
+    assert_eq!(host.components.filter(Cf::Player).count(), 2);
+}
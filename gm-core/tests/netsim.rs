@@ -0,0 +1,58 @@
+#![allow(non_upper_case_globals)]
+
+use gm_core::{NetConditions, NetSim};
+
+// 12 seconds of play, long enough for a few desync checkpoints
+const PlayTime: u32 = 12_000;
+
+fn assert_in_sync(sim: &NetSim) {
+    assert!(sim.confirmed_frames() > 100, "only {} frames confirmed", sim.confirmed_frames());
+    assert_eq!(sim.first_mismatch(), None);
+    for client in &sim.clients {
+        assert!(!client.game.desync.desynced(), "slot {} saw a desync", client.slot);
+    }
+}
+
+#[test]
+fn perfect_network() {
+    let mut sim = NetSim::new(1234, &[NetConditions::perfect(); 4]);
+    sim.run(PlayTime);
+    assert_in_sync(&sim);
+}
+
+#[test]
+fn latency_and_jitter() {
+    let conditions = [
+        NetConditions { latency: 20, jitter: 10, ..Default::default() },
+        NetConditions { latency: 80, jitter: 40, ..Default::default() },
+        NetConditions { latency: 150, jitter: 100, ..Default::default() },
+    ];
+    let mut sim = NetSim::new(99, &conditions);
+    sim.run(PlayTime);
+    assert_in_sync(&sim);
+}
+
+#[test]
+fn loss_and_dropped_slots() {
+    let conditions = [
+        NetConditions { latency: 30, jitter: 20, loss: 10, dropped: 0 },
+        NetConditions { latency: 60, jitter: 0, loss: 0, dropped: 25 },
+        NetConditions { latency: 40, jitter: 60, loss: 20, dropped: 10 },
+        NetConditions::perfect(),
+        NetConditions { latency: 100, jitter: 30, loss: 5, dropped: 5 },
+    ];
+    let mut sim = NetSim::new(7, &conditions);
+    sim.run(PlayTime);
+    assert_in_sync(&sim);
+}
+
+// the harness has to be able to fail
+#[test]
+fn detects_divergence() {
+    let mut sim = NetSim::new(1234, &[NetConditions::perfect(); 3]);
+    sim.run(3_000);
+    sim.clients[2].game.rand.next_u32();
+    sim.run(PlayTime);
+    assert!(sim.first_mismatch().is_some());
+    assert!(sim.clients[0].game.desync.desynced());
+}
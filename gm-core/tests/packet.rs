@@ -0,0 +1,61 @@
+mod common;
+
+use gm_core::{FramePacket, Game, MersenneTwister, PacketError};
+
+fn filled() -> FramePacket {
+    let mut rand = MersenneTwister::from(6);
+    let mut packet = FramePacket::new();
+    packet.connected = 0b1011;
+    packet.dropped = 0b0010;
+    for raw in packet.raw.iter_mut() {
+        *raw = common::keys(&mut rand) | Game::XMask.encode(i64::from(rand.next_u32() & 0xffff));
+    }
+    // the highest bit that fits
+    packet.raw[Game::SlotCount - 1] = 1 << 55;
+    packet
+}
+
+#[test]
+fn round_trip() {
+    let packet = filled();
+    let bytes = packet.encode();
+    assert_eq!(bytes.len(), FramePacket::Size);
+    assert!(FramePacket::decode(&bytes).unwrap() == packet);
+    assert!(FramePacket::decode(&FramePacket::new().encode()).unwrap() == FramePacket::new());
+}
+
+#[test]
+fn wrong_lengths_are_rejected() {
+    let bytes = filled().encode();
+    let expected = FramePacket::Size;
+    for found in [0, FramePacket::HeaderSize, expected - 1] {
+        assert_eq!(FramePacket::decode(&bytes[..found]).err(), Some(PacketError::WrongLength { found, expected }));
+    }
+    let mut long = bytes.clone();
+    long.push(0);
+    assert_eq!(FramePacket::decode(&long).err(), Some(PacketError::WrongLength { found: expected + 1, expected }));
+
+    // a game left as it was
+    let mut game = common::start(2, 3);
+    let before = game.checksum();
+    assert!(game.read_packet(&long).is_err());
+    assert_eq!(game.checksum(), before);
+}
+
+#[test]
+fn inputs_lose_their_top_byte() {
+    let raw = Game::SyncedMask.encode(1) | Game::XMask.encode(1234);
+    let bytes = FramePacket::encode_input(raw | 0x7f << 56);
+    assert_eq!(bytes.len(), FramePacket::InputSize);
+    assert_eq!(FramePacket::decode_input(&bytes), Ok(raw));
+    assert_eq!(FramePacket::decode_input(&bytes[1..]), Err(PacketError::WrongLength { found: 6, expected: 7 }));
+}
+
+#[test]
+fn slots_round_trip() {
+    let packet = filled();
+    let mut slots = common::players(0);
+    packet.apply(&mut slots);
+    assert!(slots[1].broken && slots[3].connected && !slots[2].connected);
+    assert!(FramePacket::from_slots(&slots) == packet);
+}
@@ -0,0 +1,169 @@
+mod common;
+
+use gm_core::{Desync, Game, MersenneTwister, Neutral, PredictionMode, PredictionStats, Predictor, ReleaseFire, RepeatLast, Slot, predict_remote, predictor_from_id};
+
+// always firing, with some frames dropped
+fn press(game: &mut Game, rand: &mut MersenneTwister) {
+    common::press(&mut game.slots, 4, rand);
+    for slot in game.slots.iter_mut().take(4) {
+        slot.raw |= Game::PrimaryMask.encode(1);
+        slot.broken = rand.next_u32().is_multiple_of(9);
+    }
+}
+
+// a forward copy fed the inputs that later get confirmed, from every frame
+// of a match, has to land exactly where the confirmed game does
+fn forward_matches(mode: PredictionMode) -> bool {
+    let mut game = common::start(4, 13);
+    let mut forward = Game::new();
+    forward.predictionMode = mode;
+    let mut rand = MersenneTwister::from(2);
+    let mut same = true;
+
+    for _ in 0..40 {
+        forward.smartCopy(&game);
+        let mut inputs = rand;
+        let mut predicted = Vec::new();
+        for _ in 0..8 {
+            press(&mut forward, &mut inputs);
+            forward.fastForward();
+            predicted.push(forward.checksum());
+        }
+        for sum in predicted {
+            press(&mut game, &mut rand);
+            game.update();
+            same &= sum == game.checksum();
+        }
+    }
+    same
+}
+
+#[test]
+fn full_prediction_is_bit_identical() {
+    assert!(forward_matches(PredictionMode::Full));
+}
+
+// otherwise the test above proves nothing
+#[test]
+fn fast_prediction_is_not() {
+    assert!(!forward_matches(PredictionMode::Fast));
+}
+
+fn holding(raw: i64) -> Slot {
+    let mut slot = common::players(1)[0];
+    slot.raw = raw;
+    slot
+}
+
+fn firing() -> i64 {
+    Game::SyncedMask.encode(1) | Game::LeftMask.encode(1) | Game::PrimaryMask.encode(1)
+}
+
+#[test]
+fn neutral_only_stays_in_game() {
+    assert_eq!(Neutral.predict(&holding(firing()), 1), Game::SyncedMask.encode(1));
+    assert_eq!(Neutral.predict(&holding(Game::LeftMask.encode(1)), 5), 0);
+}
+
+#[test]
+fn release_fire_lets_go_after_its_frames() {
+    let release = ReleaseFire { frames: 2 };
+    let last = holding(firing());
+    assert_eq!(release.predict(&last, 1), firing());
+    assert_eq!(release.predict(&last, 2), firing());
+    assert_eq!(release.predict(&last, 3), firing() & !Game::PrimaryMask.mask);
+    assert_eq!(ReleaseFire { frames: 0 }.predict(&last, 1), Game::SyncedMask.encode(1) | Game::LeftMask.encode(1));
+}
+
+#[test]
+fn predictors_by_id() {
+    let last = holding(firing());
+    assert_eq!(predictor_from_id(0, 0).unwrap().predict(&last, 4), RepeatLast.predict(&last, 4));
+    assert_eq!(predictor_from_id(1, 0).unwrap().predict(&last, 4), Neutral.predict(&last, 4));
+    assert_eq!(predictor_from_id(2, 3).unwrap().predict(&last, 3), firing());
+    assert_eq!(predictor_from_id(2, 3).unwrap().predict(&last, 4), ReleaseFire { frames: 3 }.predict(&last, 4));
+    assert!(predictor_from_id(2, -1).is_none());
+    assert!(predictor_from_id(3, 0).is_none());
+    assert!(predictor_from_id(-1, 0).is_none());
+}
+
+#[test]
+fn predict_remote_keeps_the_local_slot() {
+    let mut last = common::players(3);
+    for slot in last.iter_mut().take(3) {
+        slot.raw = firing();
+        slot.broken = true;
+    }
+    let mut inputs = common::players(3);
+    inputs[1].raw = Game::RightMask.encode(1) | Game::SyncedMask.encode(1);
+    predict_remote(&Neutral, &mut inputs, &last, 1, 1);
+
+    assert_eq!(inputs[1].raw, Game::RightMask.encode(1) | Game::SyncedMask.encode(1));
+    for i in [0, 2] {
+        assert_eq!(inputs[i].raw, Game::SyncedMask.encode(1));
+        assert!(inputs[i].connected);
+        assert!(!inputs[i].broken);
+    }
+    // disconnected slots follow the last frame too
+    assert!(!inputs[3].connected);
+    assert_eq!(inputs[3].raw, 0);
+}
+
+#[test]
+fn stats_count_connected_remote_slots() {
+    let mut stats = PredictionStats::new();
+    assert_eq!(stats.accuracy(), 1.0);
+
+    let mut confirmed = common::players(3);
+    for slot in confirmed.iter_mut() {
+        slot.raw = firing();
+    }
+    let mut predicted = confirmed.clone();
+    // stamps are never guessed
+    predicted[0].raw |= Desync::FlagMask.encode(1) | Desync::SumMask.encode(5);
+    // the local slot and empty slots are not counted
+    predicted[1].raw = 0;
+    predicted[5].raw = 0;
+    predicted[2].raw = Game::SyncedMask.encode(1);
+    stats.record(&predicted, &confirmed, 1);
+
+    assert_eq!(stats.predicted, 2);
+    assert_eq!(stats.correct, 1);
+    assert_eq!(stats.accuracy(), 0.5);
+}
+
+// one player holds a key for a while, the other changes every frame
+fn history(frames: u32) -> Vec<Vec<Slot>> {
+    let mut rand = MersenneTwister::from(4);
+    let mut slots = common::players(2);
+    let mut ret = Vec::new();
+    for frame in 0..frames {
+        slots[0].raw = Game::SyncedMask.encode(1) | Game::LeftMask.encode(i64::from(frame / 10 % 2));
+        common::press(&mut slots[1..], 1, &mut rand);
+        ret.push(slots.clone());
+    }
+    ret
+}
+
+#[test]
+fn evaluate_replays_a_history() {
+    let history = history(100);
+    // slot 1 is local, slot 0 only changes every tenth frame
+    let repeat = PredictionStats::evaluate(&RepeatLast, &history, 1, 1);
+    assert_eq!(repeat.predicted, 99);
+    assert_eq!(repeat.correct, 90);
+
+    let further = PredictionStats::evaluate(&RepeatLast, &history, 1, 4);
+    assert_eq!(further.predicted, 96);
+    assert!(further.accuracy() < repeat.accuracy());
+
+    let neutral = PredictionStats::evaluate(&Neutral, &history, 1, 1);
+    assert_eq!(neutral.correct, 49);
+
+    // slot 1 is remote as well now
+    let both = PredictionStats::evaluate(&RepeatLast, &history, 2, 1);
+    assert_eq!(both.predicted, 198);
+    assert!(both.correct > 90 && both.correct < 198);
+
+    assert_eq!(PredictionStats::evaluate(&RepeatLast, &history[..1], 1, 1).predicted, 0);
+}
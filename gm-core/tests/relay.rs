@@ -0,0 +1,86 @@
+use gm_core::{FramePacket, Game, RelayRoom};
+
+#[test]
+fn join_and_leave() {
+    let mut room = RelayRoom::new();
+    assert!(room.is_empty());
+    assert_eq!(room.join(), Some(0));
+    assert_eq!(room.join(), Some(1));
+    assert_eq!(room.join(), Some(2));
+    assert_eq!(room.connected(), 0b111);
+
+    // a freed slot is handed out again, empty
+    assert!(room.upload(1, 5));
+    room.leave(1);
+    assert_eq!(room.connected(), 0b101);
+    assert!(!room.upload(1, 6));
+    assert_eq!(room.join(), Some(1));
+    let packet = room.tick();
+    assert_eq!(packet.connected, 0b111);
+    assert_eq!(packet.raw[1], 0);
+
+    room.leave(0);
+    room.leave(1);
+    room.leave(2);
+    assert!(room.is_empty());
+    assert_eq!(room.tick().connected, 0);
+    assert_eq!(room.tick, 2);
+}
+
+#[test]
+fn full_room() {
+    let mut room = RelayRoom::new();
+    for n in 0..Game::SlotCount {
+        assert_eq!(room.join(), Some(n.try_into().unwrap()));
+    }
+    assert_eq!(room.join(), None);
+    assert!(!room.upload(Game::SlotCount.try_into().unwrap(), 1));
+}
+
+#[test]
+fn dropped_slots_repeat_their_last_input() {
+    let mut room = RelayRoom::new();
+    room.join();
+    room.join();
+    assert!(room.upload(0, 10));
+    assert!(room.upload(1, 20));
+    assert!(room.upload(1, 21));
+
+    let packet = room.tick();
+    assert_eq!((packet.dropped, packet.raw[0], packet.raw[1]), (0, 10, 20));
+    // slot 0 has nothing queued
+    let packet = room.tick();
+    assert_eq!((packet.dropped, packet.raw[0], packet.raw[1]), (0b01, 10, 21));
+    // slot 1 is held back and keeps its upload for later
+    assert!(room.upload(0, 11));
+    assert!(room.upload(1, 22));
+    let packet = room.tick_holding(0b10);
+    assert_eq!((packet.dropped, packet.raw[0], packet.raw[1]), (0b10, 11, 21));
+    let packet = room.tick();
+    assert_eq!((packet.dropped, packet.raw[0], packet.raw[1]), (0b01, 11, 22));
+
+    // only what fits in a packet is kept
+    assert!(room.upload(0, -1));
+    assert_eq!(room.tick().raw[0], FramePacket::InputMask);
+}
+
+#[test]
+fn uploads_are_capped() {
+    let mut room = RelayRoom::new();
+    room.join();
+    for n in 0..RelayRoom::MaxQueued {
+        assert!(room.upload(0, n.try_into().unwrap()));
+    }
+    assert!(!room.upload(0, 99));
+
+    // one tick makes room for one more
+    assert_eq!(room.tick().raw[0], 0);
+    assert!(room.upload(0, 99));
+    assert!(!room.upload(0, 100));
+    for _ in 1..RelayRoom::MaxQueued {
+        room.tick();
+    }
+    let packet = room.tick();
+    assert_eq!((packet.dropped, packet.raw[0]), (0, 99));
+    assert_eq!(room.tick().dropped, 1);
+}
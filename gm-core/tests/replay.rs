@@ -0,0 +1,133 @@
+mod common;
+
+use common::players;
+use gm_core::{BrokenInput, Game, MersenneTwister, Replay, ReplayError, ReplayPlayer, RollbackSession, Slot, Steering};
+
+// with some frames dropped
+fn press(slots: &mut [Slot], rand: &mut MersenneTwister) {
+    common::press(slots, 3, rand);
+    for slot in slots.iter_mut().take(3) {
+        slot.broken = rand.next_u32().is_multiple_of(11);
+    }
+}
+
+// a recorded match and the checksum after every frame of it, 0 being init
+fn record(frames: u32, keyframes: u32) -> (Replay, Vec<u64>) {
+    let mut game = Game::new();
+    game.slots.copy_from_slice(&players(3));
+    game.steering = Steering::Keys;
+    game.brokenInput = BrokenInput::Neutral;
+    game.init(31);
+    let mut replay = Replay::new(31, &game.slots);
+    replay.steering = game.steering;
+    replay.brokenInput = game.brokenInput;
+    replay.keyframeInterval = keyframes;
+
+    let mut rand = MersenneTwister::from(8);
+    let mut sums = vec![game.checksum()];
+    for _ in 0..frames {
+        press(&mut game.slots, &mut rand);
+        game.update();
+        replay.record(&game);
+        sums.push(game.checksum());
+    }
+    (replay, sums)
+}
+
+#[test]
+fn round_trip() {
+    let (replay, sums) = record(300, 64);
+    let bytes = replay.encode();
+    let decoded = Replay::decode(&bytes).unwrap();
+    assert_eq!(decoded.seed, replay.seed);
+    assert_eq!(decoded.connected, replay.connected);
+    assert_eq!(decoded.connectedAtStart, replay.connectedAtStart);
+    assert!(decoded.steering == Steering::Keys);
+    assert!(decoded.brokenInput == BrokenInput::Neutral);
+    assert_eq!(decoded.keyframes, replay.keyframes);
+    assert_eq!(decoded.encode(), bytes);
+
+    let mut player = ReplayPlayer::new(decoded);
+    assert_eq!(player.game().checksum(), sums[0]);
+    while player.step() {
+        assert_eq!(player.game().checksum(), sums[player.frame()], "frame {}", player.frame());
+    }
+    assert_eq!(player.frame(), 300);
+}
+
+#[test]
+fn rejects_damaged_files() {
+    let (replay, _) = record(50, 16);
+    let bytes = replay.encode();
+
+    let mut magic = bytes.clone();
+    magic[1] = b'X';
+    assert!(matches!(Replay::decode(&magic), Err(ReplayError::BadMagic)));
+    for version in [1, Replay::VERSION - 1, Replay::VERSION + 1] {
+        let mut other = bytes.clone();
+        other[4..6].copy_from_slice(&version.to_le_bytes());
+        assert!(matches!(Replay::decode(&other), Err(ReplayError::WrongVersion { found, .. }) if found == version));
+    }
+    assert!(matches!(Replay::decode(&bytes[..bytes.len() - 1]), Err(ReplayError::Truncated)));
+    let mut longer = bytes.clone();
+    longer.push(0);
+    assert!(matches!(Replay::decode(&longer), Err(ReplayError::TrailingBytes(1))));
+}
+
+// seeking has to land exactly where playing straight through does, with or
+// without a keyframe to start from
+#[test]
+fn seek_matches_playback() {
+    for keyframes in [0, 40] {
+        let (replay, sums) = record(300, keyframes);
+        let mut player = ReplayPlayer::new(replay);
+
+        for target in [120, 45, 299, 0, 81, 300, 1000, 200] {
+            player.seek(target).unwrap();
+            let at = target.min(300);
+            assert_eq!(player.frame(), at);
+            assert_eq!(player.game().checksum(), sums[at], "seek to {} with keyframes {}", target, keyframes);
+        }
+
+        // at 200, over the keyframe at 160 and back
+        for at in (150..200).rev() {
+            assert!(player.step_back().unwrap());
+            assert_eq!(player.frame(), at);
+            assert_eq!(player.game().checksum(), sums[at], "step back to {} with keyframes {}", at, keyframes);
+        }
+        player.seek(0).unwrap();
+        assert!(!player.step_back().unwrap());
+    }
+}
+
+// a rollback client records the confirmed state, not the predicted one
+#[test]
+fn recording_a_rollback_session() {
+    let mut session = RollbackSession::new(0, RollbackSession::DefaultCapacity);
+    session.init(31, &players(3));
+    let mut lockstep = Game::new();
+    lockstep.slots.copy_from_slice(&players(3));
+    lockstep.init(31);
+    let mut replay = Replay::new(31, &players(3));
+    replay.keyframeInterval = 50;
+
+    let mut rand = MersenneTwister::from(12);
+    let mut slots = players(3);
+    for _ in 0..200 {
+        while session.can_predict() {
+            session.predict(slots[0].raw);
+        }
+        press(&mut slots, &mut rand);
+        session.confirm(&slots);
+        replay.record(session.confirmed_game());
+        common::receive(&mut lockstep, &slots);
+        lockstep.update();
+    }
+
+    let mut player = ReplayPlayer::new(replay);
+    player.seek(200).unwrap();
+    assert_eq!(player.game().checksum(), lockstep.checksum());
+    player.seek(120).unwrap();
+    player.seek(200).unwrap();
+    assert_eq!(player.game().checksum(), lockstep.checksum());
+}
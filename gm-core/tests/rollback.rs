@@ -0,0 +1,83 @@
+mod common;
+
+use common::players;
+use gm_core::{Desync, Game, RollbackSession};
+
+// keys held for `hold` frames at a time, different for every slot
+fn keys(frame: u32, slot: u32, hold: u32) -> i64 {
+    let h = (frame / hold + slot * 1000).wrapping_mul(2654435761) >> 7;
+    Game::SyncedMask.encode(1)
+        | Game::LeftMask.encode(i64::from(h & 1))
+        | Game::RightMask.encode(i64::from((h >> 1) & 1))
+        | Game::PrimaryMask.encode(i64::from((h >> 2) & 1))
+}
+
+// slot 0 is local and runs `ahead` frames in front of the server, slots 1 and 2
+// stamp their uploads like real clients do
+fn play(hold: u32, ahead: u32) -> RollbackSession {
+    let mut lockstep = Game::new();
+    lockstep.slots.copy_from_slice(&players(3));
+    lockstep.init(17);
+    let mut session = RollbackSession::new(0, RollbackSession::DefaultCapacity);
+    session.init(17, &players(3));
+
+    for frame in 1..=250 {
+        while session.head_frame() < frame + ahead {
+            let next = session.head_frame() + 1;
+            assert!(session.predict(keys(next, 0, 5)));
+        }
+
+        let mut slots = players(3);
+        slots[0].raw = keys(frame, 0, 5);
+        for (n, slot) in (1..).zip(slots.iter_mut().take(3).skip(1)) {
+            slot.raw = lockstep.desync.stamp(keys(frame, n, hold));
+        }
+        lockstep.slots.copy_from_slice(&slots);
+        lockstep.update();
+        session.confirm(&slots);
+
+        assert_eq!(session.confirmed_frame(), frame);
+        assert_eq!(session.confirmed_game().checksum(), lockstep.checksum(), "frame {}", frame);
+    }
+    assert!(!session.game().desync.desynced());
+    session
+}
+
+#[test]
+fn mispredictions_roll_back_to_lockstep() {
+    let session = play(3, 4);
+    assert!(session.rollbacks() > 10);
+    assert!(session.resimulated() > session.rollbacks());
+    assert!(session.stats().accuracy() < 1.0);
+}
+
+// the stamps change every checkpoint, but nothing the game reads does
+#[test]
+fn stamps_are_not_mispredictions() {
+    let session = play(1000, 4);
+    // only the very first guesses, made before anything was confirmed
+    assert_eq!(session.rollbacks(), 1);
+
+    // and there were stamps to get wrong
+    assert!(Desync::FlagMask.decode(session.confirmed_game().slots[1].raw) == 1);
+}
+
+#[test]
+fn stops_when_the_ring_is_full() {
+    let mut session = RollbackSession::new(0, 4);
+    session.init(3, &players(3));
+    for n in 1..=4 {
+        assert!(session.can_predict());
+        assert!(session.predict(keys(n, 0, 5)));
+    }
+    assert!(!session.can_predict());
+    assert!(!session.predict(keys(5, 0, 5)));
+    assert_eq!(session.head_frame(), 4);
+
+    let mut slots = players(3);
+    slots[0].raw = keys(1, 0, 5);
+    session.confirm(&slots);
+    assert!(session.can_predict());
+    assert!(session.predict(keys(5, 0, 5)));
+    assert_eq!(session.head_frame(), 5);
+}
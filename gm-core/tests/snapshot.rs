@@ -0,0 +1,115 @@
+mod common;
+
+use gm_core::{Game, MersenneTwister, SnapshotError, SnapshotWriter};
+
+fn start() -> Game {
+    common::start(4, 21)
+}
+
+fn play(game: &mut Game, rand: &mut MersenneTwister, frames: u32) {
+    common::play(game, 4, rand, frames);
+}
+
+#[test]
+fn round_trip() {
+    let mut game = start();
+    let mut rand = MersenneTwister::from(4);
+    play(&mut game, &mut rand, 200);
+    let bytes = game.save_snapshot();
+
+    let mut loaded = Game::new();
+    loaded.load_snapshot(&bytes).unwrap();
+    assert_eq!(loaded.checksum(), game.checksum());
+    assert_eq!(loaded.save_snapshot(), bytes);
+
+    // and it plays on exactly the same
+    let mut other = rand;
+    play(&mut game, &mut rand, 200);
+    play(&mut loaded, &mut other, 200);
+    assert_eq!(loaded.checksum(), game.checksum());
+}
+
+#[test]
+fn frame_counter() {
+    let mut game = start();
+    let mut rand = MersenneTwister::from(4);
+    assert_eq!(game.frame, 0);
+    for frame in 1..=50 {
+        play(&mut game, &mut rand, 1);
+        assert_eq!(game.frame, frame);
+        // every slot, connected or not, ran with this frame's inputs
+        assert!(game.slots.iter().all(|slot| slot.frame == frame));
+    }
+
+    let mut loaded = Game::new();
+    loaded.load_snapshot(&game.save_snapshot()).unwrap();
+    assert_eq!(loaded.frame, 50);
+    assert!(loaded.slots.iter().all(|slot| slot.frame == 50));
+    play(&mut loaded, &mut rand, 1);
+    assert_eq!(loaded.frame, 51);
+    assert_eq!(loaded.slots[0].frame, 51);
+
+    // it is part of the state
+    let before = game.checksum();
+    game.frame += 1;
+    assert_ne!(game.checksum(), before);
+
+    game.init(21);
+    assert_eq!(game.frame, 0);
+}
+
+fn rejected(bytes: &[u8]) -> SnapshotError {
+    let mut game = start();
+    let before = game.checksum();
+    let e = game.load_snapshot(bytes).unwrap_err();
+    // a failed load leaves the game alone
+    assert_eq!(game.checksum(), before);
+    e
+}
+
+#[test]
+fn rejects_bad_headers() {
+    let mut game = start();
+    play(&mut game, &mut MersenneTwister::from(5), 100);
+    let bytes = game.save_snapshot();
+
+    let mut magic = bytes.clone();
+    magic[0] = b'X';
+    assert_eq!(rejected(&magic), SnapshotError::BadMagic);
+    assert_eq!(rejected(&bytes[..3]), SnapshotError::BadMagic);
+    assert_eq!(rejected(&[]), SnapshotError::BadMagic);
+
+    for version in [0, SnapshotWriter::VERSION - 1, SnapshotWriter::VERSION + 1] {
+        let mut old = bytes.clone();
+        old[4..6].copy_from_slice(&version.to_le_bytes());
+        assert_eq!(rejected(&old), SnapshotError::WrongVersion { found: version, expected: SnapshotWriter::VERSION });
+    }
+}
+
+#[test]
+fn rejects_truncated_and_trailing() {
+    let mut game = start();
+    play(&mut game, &mut MersenneTwister::from(6), 100);
+    let bytes = game.save_snapshot();
+
+    // every cut lands somewhere different in the layout
+    for len in (5..bytes.len()).step_by(37).chain([bytes.len() - 1]) {
+        assert_eq!(rejected(&bytes[..len]), SnapshotError::Truncated, "cut at {}", len);
+    }
+
+    let mut longer = bytes.clone();
+    longer.extend_from_slice(&[0, 0, 0]);
+    assert_eq!(rejected(&longer), SnapshotError::TrailingBytes(3));
+}
+
+#[test]
+fn rejects_an_index_table_allocate_cannot_grow() {
+    let mut game = start();
+    game.components.manager.list.resize(usize::from(u16::MAX) + 1, 0);
+    assert_eq!(rejected(&game.save_snapshot()), SnapshotError::Invalid("index table too long"));
+
+    // one shorter is still fine
+    game.components.manager.list.truncate(usize::from(u16::MAX));
+    let mut loaded = Game::new();
+    loaded.load_snapshot(&game.save_snapshot()).unwrap();
+}
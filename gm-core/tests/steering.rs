@@ -0,0 +1,81 @@
+mod common;
+
+use gm_core::{Cf, Fixed, Game, Steering};
+
+fn start(steering: Steering, count: usize) -> Game {
+    let mut game = Game::new();
+    game.steering = steering;
+    game.slots.copy_from_slice(&common::players(count));
+    game.init(8);
+    game
+}
+
+fn ship_x(game: &mut Game, slot: usize) -> Fixed {
+    game.components.filter(Cf::Active | Cf::Player)
+        .find(|r| usize::try_from(r.player.slot).unwrap() == slot)
+        .unwrap()
+        .body.position.x
+}
+
+fn towards(x: i32) -> i64 {
+    Game::SyncedMask.encode(1) | Game::XMask.encode(x.into()) | Game::SteerMask.encode(1)
+}
+
+// one frame with these inputs, then how far each of the first `count` ships moved
+fn step(game: &mut Game, raws: &[i64]) -> Vec<Fixed> {
+    let before: Vec<Fixed> = (0..raws.len()).map(|n| ship_x(game, n)).collect();
+    for (slot, raw) in game.slots.iter_mut().zip(raws) {
+        slot.raw = *raw;
+    }
+    game.update();
+    before.iter().enumerate().map(|(n, x)| ship_x(game, n) - *x).collect()
+}
+
+#[test]
+fn capped_without_overshoot() {
+    let mut game = start(Steering::Target, 1);
+    let home = ship_x(&mut game, 0);
+
+    // right, then back left; the ship starts close to the left edge
+    for offset in [64, 23, -3] {
+        let target = home + Fixed::from_int(offset);
+        let from = ship_x(&mut game, 0);
+        let mut frames: u32 = 0;
+        while ship_x(&mut game, 0) != target {
+            let left = target - ship_x(&mut game, 0);
+            let moved = step(&mut game, &[towards(target.to_int())])[0];
+            assert!(moved.abs() <= Game::MaxSteer);
+            // all the way when it can, and never past
+            assert_eq!(moved, left.clamp(-Game::MaxSteer, Game::MaxSteer));
+            frames += 1;
+            assert!(frames < 20);
+        }
+        assert_eq!(frames, (target - from).to_int().unsigned_abs().div_ceil(Game::MaxSteer.to_int().unsigned_abs()));
+        // and stays there
+        assert_eq!(step(&mut game, &[towards(target.to_int())])[0], Fixed::Zero);
+    }
+}
+
+#[test]
+fn per_player_or_per_match() {
+    let keys = Game::SyncedMask.encode(1) | Game::RightMask.encode(1);
+    let far = |game: &mut Game| (ship_x(game, 0) - Fixed::from_int(100)).to_int();
+
+    // each picks for themselves: one steers to the left, the other holds right
+    let mut game = start(Steering::PerPlayer, 2);
+    let target = far(&mut game);
+    let moved = step(&mut game, &[towards(target), keys | Game::XMask.encode(target.into())]);
+    assert_eq!(moved, [-Game::MaxSteer, Game::MaxSteer]);
+
+    // the steer bit is ignored, only keys move
+    let mut game = start(Steering::Keys, 2);
+    let target = far(&mut game);
+    let moved = step(&mut game, &[towards(target), keys | Game::SteerMask.encode(1)]);
+    assert_eq!(moved, [Fixed::Zero, Game::MaxSteer]);
+
+    // everyone steers, keys or not
+    let mut game = start(Steering::Target, 2);
+    let target = far(&mut game);
+    let moved = step(&mut game, &[towards(target) & !Game::SteerMask.mask, keys | Game::XMask.encode(target.into())]);
+    assert_eq!(moved, [-Game::MaxSteer, -Game::MaxSteer]);
+}
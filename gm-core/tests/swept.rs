@@ -0,0 +1,82 @@
+use gm_core::{Bounds, Cf, Data, Entity, Event, Fixed, FixedVector2, Game, ObjType, Vector2};
+
+// ended the frame at `p` after moving `d`
+fn body(p: (i32, i32), d: (i32, i32), s: (i32, i32)) -> Bounds {
+    Bounds::from(Entity::new(), 0, Vector2::from(p.0, p.1), Vector2::from(d.0, d.1), Vector2::from(s.0, s.1))
+}
+
+#[test]
+fn fast_bodies_do_not_tunnel() {
+    let wall = body((0, 0), (0, 0), (2, 10));
+    // from x = -40 to x = 40 in one frame, never overlapping at either end
+    let shot = body((40, 0), (80, 0), (2, 2));
+    assert_eq!(shot.sweep(&wall), Some(Fixed::from_ratio(36, 80)));
+    assert_eq!(wall.sweep(&shot), Some(Fixed::from_ratio(36, 80)));
+
+    // the same path a little higher goes over the top
+    let high = body((40, 13), (80, 0), (2, 2));
+    assert_eq!(high.sweep(&wall), None);
+
+    // stops short
+    let short = body((-10, 0), (30, 0), (2, 2));
+    assert_eq!(short.sweep(&wall), None);
+}
+
+#[test]
+fn still_and_moving_apart() {
+    let a = body((0, 0), (0, 0), (5, 5));
+    let b = body((10, 0), (0, 0), (5, 5));
+    // touching edges count, like Bounds::overlap
+    assert_eq!(a.sweep(&b), Some(Fixed::Zero));
+    assert_eq!(a.sweep(&body((11, 0), (0, 0), (5, 5))), None);
+
+    // overlapping at the start, apart at the end
+    let leaving = body((30, 0), (25, 0), (5, 5));
+    assert_eq!(a.sweep(&leaving), Some(Fixed::Zero));
+}
+
+#[test]
+fn both_moving() {
+    // head on, from -50 and 50 to 0 and 0
+    let a = body((0, 0), (50, 0), (5, 5));
+    let b = body((0, 0), (-50, 0), (5, 5));
+    assert_eq!(a.sweep(&b), Some(Fixed::from_ratio(90, 100)));
+
+    // side by side at the same speed never meet
+    let c = body((0, 20), (50, 0), (5, 5));
+    assert_eq!(a.sweep(&c), None);
+
+    // diagonal, the later axis decides
+    let d = body((20, 20), (20, 20), (1, 1));
+    let e = body((10, 0), (0, 0), (1, 1));
+    assert_eq!(d.sweep(&e), None);
+    let f = body((10, 10), (0, 0), (1, 1));
+    assert_eq!(d.sweep(&f), Some(Fixed::from_ratio(8, 20)));
+}
+
+fn enemies(game: &mut Game) -> Vec<(i32, u16)> {
+    game.components.filter(Cf::Active | Cf::Enemy)
+        .map(|r| (r.body.position.y.to_int(), r.animator.frame))
+        .collect()
+}
+
+#[test]
+fn fast_shot_hits_the_nearest_enemy() {
+    let mut game = Game::new();
+    game.init(1);
+    game.eventList.push(Event::from_entity(ObjType::Enemy, FixedVector2::from_int(0, 50)));
+    game.eventList.push(Event::from_entity(ObjType::Enemy, FixedVector2::from_int(0, 0)));
+    // far faster than anything in the game, straight through both
+    game.eventList.push(Event::from_shot(ObjType::Bullet, FixedVector2::from_int(0, -100), FixedVector2::from_int(0, 300)));
+    game.resolveEvents();
+
+    game.integrate();
+    game.fillContactList();
+    let times: Vec<Fixed> = game.eventList.iter().map(|e| e.time).collect();
+    assert_eq!(times, [Fixed::from_ratio(70, 300), Fixed::from_ratio(120, 300)]);
+    game.resolveEvents();
+
+    let hit = enemies(&mut game);
+    assert!(hit.contains(&(0, Data::_null)));
+    assert!(!hit.contains(&(50, Data::_null)));
+}
@@ -0,0 +1,108 @@
+use gm_core::{Angle, Cf, Checksum, Event, Fixed, FixedVector2, Game, ObjType};
+
+fn radians(a: Angle) -> f64 {
+    f64::from(a.to_bits()) * std::f64::consts::TAU / 65536.0
+}
+
+fn bits(v: f64) -> i64 {
+    (v * 65536.0).round() as i64
+}
+
+#[test]
+fn cardinal_angles_are_exact() {
+    assert_eq!(Angle::Right.cos(), Fixed::One);
+    assert_eq!(Angle::Right.sin(), Fixed::Zero);
+    assert_eq!(Angle::Up.sin(), Fixed::One);
+    assert_eq!(Angle::Up.cos(), Fixed::Zero);
+    assert_eq!(Angle::Left.cos(), -Fixed::One);
+    assert_eq!(Angle::Down.sin(), -Fixed::One);
+    assert_eq!(Angle::from_degrees(90), Angle::Up);
+    assert_eq!(Angle::from_degrees(-90), Angle::Down);
+    assert_eq!(Angle::from_degrees(450), Angle::Up);
+}
+
+#[test]
+fn sin_and_cos_are_close() {
+    for a in 0..=u16::MAX {
+        let angle = Angle::from_bits(a);
+        let r = radians(angle);
+        assert!((i64::from(angle.sin().to_bits()) - bits(r.sin())).abs() <= 2, "sin {}", a);
+        assert!((i64::from(angle.cos().to_bits()) - bits(r.cos())).abs() <= 2, "cos {}", a);
+    }
+}
+
+// the reference value pins every result, so a platform that disagrees fails here
+#[test]
+fn bit_identical() {
+    let mut sum = Checksum::new();
+    for a in 0..=u16::MAX {
+        let angle = Angle::from_bits(a);
+        sum.write_i32(angle.sin().to_bits());
+        sum.write_i32(angle.cos().to_bits());
+    }
+    for v in [1, 2, 3, 100, 65536, 1 << 20, i32::MAX] {
+        sum.write_i32(Fixed::from_bits(v).sqrt().to_bits());
+        sum.write_u16(Angle::atan2(Fixed::from_bits(v), Fixed::from_bits(-v / 3)).to_bits());
+    }
+    assert_eq!(sum.value(), 0x65637faafa211437);
+}
+
+#[test]
+fn atan2_inverts_sin_and_cos() {
+    let length = Fixed::from_int(100);
+    for a in (0..=u16::MAX).step_by(97) {
+        let angle = Angle::from_bits(a);
+        let v = FixedVector2::from_angle(angle, length);
+        let back = v.angle();
+        let diff = i32::from((back - angle).to_bits() as i16);
+        assert!(diff.abs() <= 2, "{} came back as {}", a, back.to_bits());
+    }
+    assert_eq!(Angle::atan2(Fixed::Zero, Fixed::Zero), Angle::Right);
+    assert_eq!(Angle::atan2(Fixed::from_int(-3), Fixed::Zero), Angle::Down);
+    assert_eq!(Angle::atan2(Fixed::Zero, Fixed::from_int(-3)), Angle::Left);
+}
+
+#[test]
+fn sqrt_and_length() {
+    assert_eq!(Fixed::from_int(4).sqrt(), Fixed::from_int(2));
+    assert_eq!(Fixed::from_int(2).sqrt().to_bits(), 92681);
+    assert_eq!(Fixed::from_ratio(1, 4).sqrt(), Fixed::from_ratio(1, 2));
+    assert_eq!(Fixed::from_int(-4).sqrt(), Fixed::Zero);
+    assert_eq!(Fixed::Max.sqrt().to_int(), 181);
+    assert_eq!(FixedVector2::from_int(3, -4).length(), Fixed::from_int(5));
+    assert_eq!(FixedVector2::from(Fixed::Max, Fixed::Max).length(), Fixed::Max);
+}
+
+fn shots(game: &mut Game) -> Vec<FixedVector2> {
+    game.resolveEvents();
+    game.components.filter(Cf::Active | Cf::Body)
+        .filter(|r| r.objectId.0 == ObjType::BadBullet)
+        .map(|r| r.body.velocity)
+        .collect()
+}
+
+#[test]
+fn angled_projectiles() {
+    let mut game = Game::new();
+    game.init(1);
+    let from = FixedVector2::from_int(0, 100);
+    let speed = Fixed::from_int(8);
+
+    game.eventList.push(Event::from_aimed(ObjType::BadBullet, from, FixedVector2::from_int(300, -200), speed));
+    let aimed = shots(&mut game);
+    assert_eq!(aimed.len(), 1);
+    let off = i32::from((aimed[0].angle() - Angle::from_degrees(-45)).to_bits() as i16);
+    assert!(off.abs() <= 2);
+    assert!((aimed[0].length() - speed).abs() <= Fixed::from_bits(2));
+
+    let mut game = Game::new();
+    game.init(1);
+    let step = Angle::from_degrees(15);
+    game.eventList.extend(Event::spread(ObjType::BadBullet, from, Angle::Down, step, 3, speed));
+    let fan = shots(&mut game);
+    assert_eq!(fan.len(), 3);
+    assert_eq!(fan[1], FixedVector2::from_int(0, -8));
+    // mirror images of each other either side of straight down
+    assert_eq!(fan[0].x, -fan[2].x);
+    assert_eq!(fan[0].y, fan[2].y);
+}
@@ -0,0 +1,45 @@
+use gdnative::prelude::*;
+
+use gm_core::{InputField, InputLayout};
+
+
+/*******************************************************************
+
+    InputSchema, so scripts never copy bit positions by hand
+
+    var Schema = preload("res://InputSchema.gdns").new()
+    ret |= Schema.encode("game", "left", 1)
+
+********************************************************************/
+
+#[derive(NativeClass)]
+#[inherit(Reference)]
+pub struct InputSchema;
+
+impl InputSchema {
+    fn new(_base: &Reference) -> Self {
+        InputSchema
+    }
+
+    fn lookup(layout: &str, field: &str) -> Option<&'static InputField> {
+        let found = InputLayout::by_name(layout).and_then(|l| l.field(field));
+        if found.is_none() {
+            godot_error!("No input field {} in layout {}", field, layout);
+        }
+        found
+    }
+}
+
+#[methods]
+impl InputSchema {
+
+    #[method]
+    fn encode(&self, layout: String, field: String, value: i64) -> i64 {
+        InputSchema::lookup(&layout, &field).map_or(0, |f| f.encode(value))
+    }
+
+    #[method]
+    fn decode(&self, layout: String, field: String, raw: i64) -> i64 {
+        InputSchema::lookup(&layout, &field).map_or(0, |f| f.decode(raw))
+    }
+}
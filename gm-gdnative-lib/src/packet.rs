@@ -0,0 +1,55 @@
+use gdnative::prelude::*;
+
+use gm_core::{FramePacket, Game};
+
+
+/*******************************************************************
+
+    FrameReader, so scripts never pick a relay packet apart by hand
+
+    var Frames = preload("res://FrameReader.gdns").new()
+    var frame = Frames.decode(packet)
+    if frame != null && frame.connected[n] == true:
+
+********************************************************************/
+
+#[derive(NativeClass)]
+#[inherit(Reference)]
+pub struct FrameReader;
+
+impl FrameReader {
+    fn new(_base: &Reference) -> Self {
+        FrameReader
+    }
+}
+
+#[methods]
+impl FrameReader {
+
+    // {connected, dropped, raw} with one entry per slot, null when malformed
+    #[method]
+    fn decode(&self, bytes: ByteArray) -> Variant {
+        let packet = match FramePacket::decode(&bytes.to_vec()) {
+            Ok(packet) => packet,
+            Err(e) => {
+                godot_error!("Bad frame packet: {}", e);
+                return Variant::nil();
+            }
+        };
+
+        let connected = VariantArray::new();
+        let dropped = VariantArray::new();
+        let raw = VariantArray::new();
+        for n in 0..Game::SlotCount {
+            connected.push(packet.connected & (1 << n) != 0);
+            dropped.push(packet.dropped & (1 << n) != 0);
+            raw.push(packet.raw[n]);
+        }
+
+        let frame = Dictionary::new();
+        frame.insert("connected", connected.into_shared());
+        frame.insert("dropped", dropped.into_shared());
+        frame.insert("raw", raw.into_shared());
+        frame.into_shared().to_variant()
+    }
+}
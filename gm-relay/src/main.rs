@@ -0,0 +1,194 @@
+/*******************************************
+
+    Local relay server
+
+    Speaks the same protocol as the hosted relay:
+      ws://<address>/session/<room>
+      server -> client  1 byte, the slot assigned on join
+      client -> server  7 bytes, raw input for the next frame
+      server -> client  16 + 64*7 byte frame packet, 30 times a second
+      client -> server  a join snapshot ("GMJN..."), passed on as is to
+                        everyone else in the room, see JoinSnapshot
+
+    usage: gm-relay [address]   (default 127.0.0.1:8080)
+
+*******************************************/
+
+#![allow(non_upper_case_globals)]
+
+use std::collections::HashMap;
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tungstenite::handshake::server::{Request, Response};
+use tungstenite::{Error, Message, WebSocket};
+
+use gm_core::{FramePacket, JoinSnapshot, RelayRoom};
+
+
+const DefaultAddress: &str = "127.0.0.1:8080";
+const TickRate: u64 = 30;
+// how long a connection waits for an upload before checking for packets to send
+const PollInterval: Duration = Duration::from_millis(2);
+
+
+struct Room {
+    relay: RelayRoom,
+    peers: Vec<(u8, Sender<Vec<u8>>)>,
+}
+
+type Rooms = Arc<Mutex<HashMap<u32, Room>>>;
+
+
+// "/session/3" is room 3, anything else is room 0 like the hosted relay's default
+fn room_from_path(path: &str) -> u32 {
+    path.trim_end_matches('/')
+        .strip_prefix("/session/")
+        .and_then(|room| room.parse().ok())
+        .unwrap_or(0)
+}
+
+fn tick(rooms: &Rooms) {
+    let period = Duration::from_nanos(1_000_000_000 / TickRate);
+    let mut next = Instant::now() + period;
+    loop {
+        if let Some(wait) = next.checked_duration_since(Instant::now()) {
+            thread::sleep(wait);
+        }
+        next += period;
+
+        let mut rooms = rooms.lock().unwrap();
+        for room in rooms.values_mut() {
+            let bytes = room.relay.tick().encode();
+            for (_, peer) in &room.peers {
+                // a peer that went away is removed by its own connection thread
+                let _ = peer.send(bytes.clone());
+            }
+        }
+    }
+}
+
+fn join(rooms: &Rooms, id: u32) -> Option<(u8, Receiver<Vec<u8>>)> {
+    let mut rooms = rooms.lock().unwrap();
+    let room = rooms.entry(id).or_insert_with(|| Room { relay: RelayRoom::new(), peers: Vec::new() });
+    let slot = room.relay.join()?;
+    let (sender, receiver) = mpsc::channel();
+    // queued before the peer is visible to the ticker, so it always arrives first
+    sender.send(vec![slot]).unwrap();
+    room.peers.push((slot, sender));
+    Some((slot, receiver))
+}
+
+fn leave(rooms: &Rooms, id: u32, slot: u8) {
+    let mut rooms = rooms.lock().unwrap();
+    if let Some(room) = rooms.get_mut(&id) {
+        room.relay.leave(slot);
+        room.peers.retain(|(s, _)| *s != slot);
+        if room.relay.is_empty() {
+            rooms.remove(&id);
+        }
+    }
+}
+
+// straight away rather than on the next tick, the joiner is buffering until it arrives
+fn forward(rooms: &Rooms, id: u32, slot: u8, bytes: Vec<u8>) {
+    let rooms = rooms.lock().unwrap();
+    for (_, peer) in rooms[&id].peers.iter().filter(|(s, _)| *s != slot) {
+        let _ = peer.send(bytes.clone());
+    }
+}
+
+// tungstenite::Error is large, but it is what tungstenite hands back
+#[allow(clippy::result_large_err)]
+fn serve(rooms: &Rooms, id: u32, slot: u8, ws: &mut WebSocket<TcpStream>, outgoing: &Receiver<Vec<u8>>) -> Result<(), Error> {
+    loop {
+        while let Ok(bytes) = outgoing.try_recv() {
+            ws.send(Message::Binary(bytes))?;
+        }
+
+        match ws.read() {
+            Ok(Message::Binary(bytes)) if bytes.starts_with(&JoinSnapshot::MAGIC) => {
+                println!("room {} slot {}: join snapshot, {} bytes", id, slot, bytes.len());
+                forward(rooms, id, slot, bytes.to_vec());
+            }
+            Ok(Message::Binary(bytes)) => match FramePacket::decode_input(&bytes) {
+                Ok(raw) => {
+                    if !rooms.lock().unwrap().get_mut(&id).unwrap().relay.upload(slot, raw) {
+                        println!("room {} slot {}: too far ahead, input ignored", id, slot);
+                    }
+                }
+                Err(e) => println!("room {} slot {}: {}", id, slot, e),
+            },
+            Ok(Message::Close(_)) => return Ok(()),
+            Ok(_) => {}
+            Err(Error::Io(e)) if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut => {}
+            Err(Error::ConnectionClosed) => return Ok(()),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn connection(rooms: Rooms, stream: TcpStream) {
+    let mut path = String::new();
+    let callback = |request: &Request, response: Response| {
+        path = request.uri().path().to_string();
+        Ok(response)
+    };
+    let mut ws = match tungstenite::accept_hdr(stream, callback) {
+        Ok(ws) => ws,
+        Err(e) => {
+            println!("handshake failed: {}", e);
+            return;
+        }
+    };
+
+    let id = room_from_path(&path);
+    let Some((slot, outgoing)) = join(&rooms, id) else {
+        println!("room {} is full", id);
+        let _ = ws.close(None);
+        return;
+    };
+    println!("room {} slot {}: joined", id, slot);
+
+    let result = ws.get_ref().set_read_timeout(Some(PollInterval)).map_err(Error::Io)
+        .and_then(|_| serve(&rooms, id, slot, &mut ws, &outgoing));
+    leave(&rooms, id, slot);
+    match result {
+        Ok(()) => println!("room {} slot {}: left", id, slot),
+        Err(e) => println!("room {} slot {}: dropped ({})", id, slot, e),
+    }
+}
+
+fn main() {
+    let address = std::env::args().nth(1).unwrap_or_else(|| DefaultAddress.to_string());
+    let listener = match TcpListener::bind(&address) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("cannot listen on {}: {}", address, e);
+            std::process::exit(1);
+        }
+    };
+    println!("relay listening on ws://{}/session/<room>", address);
+
+    let rooms: Rooms = Default::default();
+    {
+        let rooms = rooms.clone();
+        thread::spawn(move || tick(&rooms));
+    }
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let _ = stream.set_nodelay(true);
+                let rooms = rooms.clone();
+                thread::spawn(move || connection(rooms, stream));
+            }
+            Err(e) => println!("accept failed: {}", e),
+        }
+    }
+}